@@ -3,7 +3,7 @@ use bevy::{
     prelude::*,
 };
 use crate::{
-    game::{GameState, MoveOneStepEvent, MoveStep},
+    game::{GameState, MoveOneStepEvent, MoveStep, RestoreStepEvent},
     resources::GameSounds,
 };
 
@@ -21,6 +21,7 @@ fn init_system(
     mut commands: Commands,
 ) {
     commands.add_observer(on_move_one_step);
+    commands.add_observer(on_restore_step);
 }
 
 fn deal_system(
@@ -35,18 +36,38 @@ fn on_move_one_step(
     mut commands: Commands,
     game_sounds: Res<GameSounds>,
 ) {
-    let audio_handle = match trigger.event().0 {
+    if let Some(handle) = get_audio_handle(trigger.event().0, &game_sounds) {
+        commands.spawn(AudioPlayer::new(handle));
+    }
+}
+
+fn on_restore_step(
+    trigger: Trigger<RestoreStepEvent>,
+    mut commands: Commands,
+    game_sounds: Res<GameSounds>,
+) {
+    if let Some(handle) = get_audio_handle(trigger.event().0, &game_sounds) {
+        commands.spawn(AudioPlayer::new(handle));
+    }
+}
+
+fn get_audio_handle(move_step: MoveStep, game_sounds: &GameSounds) -> Option<Handle<AudioSource>> {
+    match move_step {
         MoveStep::WasteToStock => Some(game_sounds.move_to_stock.clone()),
         MoveStep::StockToWaste
+        | MoveStep::StockToPile
         | MoveStep::WasteToPile
         | MoveStep::WasteToFoundation
         | MoveStep::PileToPile
         | MoveStep::PileToFoundation
-        | MoveStep::FoundationToPile => Some(game_sounds.move_card.clone()),
+        | MoveStep::FoundationToPile
+        | MoveStep::PileToFreeCell
+        | MoveStep::FreeCellToPile
+        | MoveStep::FreeCellToFoundation
+        | MoveStep::RemoveCards
+        | MoveStep::TableauToWaste
+        | MoveStep::ReserveToPile
+        | MoveStep::ReserveToFoundation => Some(game_sounds.move_card.clone()),
         MoveStep::FaceupPile => None
-    };
-
-    if let Some(handle) = audio_handle {
-        commands.spawn(AudioPlayer::new(handle));
     }
 }
@@ -0,0 +1,730 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+pub const NUM_SUIT: usize = 4;
+pub const NUM_PILES: usize = 7;
+pub const MAX_WASTES: u32 = 3;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum CardSuit {
+    Heart,
+    Diamond,
+    Club,
+    Spade,
+}
+
+impl CardSuit {
+    pub const ALL: [CardSuit; NUM_SUIT] = [
+        CardSuit::Heart,
+        CardSuit::Diamond,
+        CardSuit::Club,
+        CardSuit::Spade,
+    ];
+
+    /// 赤いスートかどうかを取得
+    pub fn is_red(&self) -> bool {
+        *self == CardSuit::Heart || *self == CardSuit::Diamond
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct BoardCard {
+    pub id: usize,
+    pub suit: CardSuit,
+    pub number: u32,
+    pub facedown: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Move {
+    StockToWaste,
+    WasteToStock,
+    WasteToPile(usize),
+    WasteToFoundation(usize),
+    PileToPile {
+        src: usize,
+        index: usize,
+        dst: usize,
+    },
+    PileToFoundation {
+        src: usize,
+        slot: usize,
+    },
+    FoundationToPile {
+        slot: usize,
+        dst: usize,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AppliedMove {
+    pub mv: Move,
+    pub num_cards: usize,
+    pub faceup: bool,
+}
+
+/// クロンダイク系の盤面（遊んでいるゲームの状態で、ヒント、ソルバー、自動完了、手詰まりの判定にも使う）
+/// 移動は apply、revert で行い、画面のカードは CardList::sync_board でこの盤面に合わせる
+/// 組札の置き場のスートは foundation_suit で決まる（2組のときは同じスートの置き場が2つある）
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Board {
+    pub stock: Vec<BoardCard>,
+    pub waste: Vec<BoardCard>,
+    pub piles: Vec<Vec<BoardCard>>,
+    pub foundations: Vec<Vec<BoardCard>>,
+    pub num_turn_to_waste: u32,
+    pub num_recycle: u32,
+    pub max_recycle: Option<u32>,
+}
+
+impl Board {
+    pub fn new(num_turn_to_waste: u32) -> Self {
+        Self::with_layout(NUM_PILES, NUM_SUIT, num_turn_to_waste)
+    }
+
+    /// 場札の列数と組札の置き場の数を指定して空の盤面を取得
+    pub fn with_layout(num_piles: usize, num_foundations: usize, num_turn_to_waste: u32) -> Self {
+        Self {
+            piles: vec![Vec::new(); num_piles],
+            foundations: vec![Vec::new(); num_foundations],
+            num_turn_to_waste,
+            ..Default::default()
+        }
+    }
+
+    /// 配布番号から配った直後の盤面を取得
+    pub fn deal(seed: u64, num_turn_to_waste: u32) -> Self {
+        let mut board = Self::new(num_turn_to_waste);
+        let mut deck = shuffled_deck(seed);
+
+        // 山札の上から場札に配る
+        let mut id = 0;
+        for index in 0..NUM_PILES {
+            for order in 0..=index {
+                if let Some((suit, number)) = deck.pop() {
+                    board.piles[index].push(BoardCard {
+                        id,
+                        suit,
+                        number,
+                        facedown: order != index,
+                    });
+                    id += 1;
+                }
+            }
+        }
+
+        // 山札は末尾が次にめくるカード
+        while let Some((suit, number)) = deck.pop() {
+            board.stock.push(BoardCard {
+                id,
+                suit,
+                number,
+                facedown: true,
+            });
+            id += 1;
+        }
+        board.stock.reverse();
+        board
+    }
+
+    /// 合法手の一覧を取得
+    pub fn legal_moves(&self) -> Vec<Move> {
+        // 組札へは置ける最初の置き場にだけ移す（同じスートの空いた置き場はどれも同じ）
+        let num_piles = self.piles.len();
+        let mut candidates: Vec<Move> = self.waste_to_foundation().into_iter()
+            .chain((0..num_piles).filter_map(|src| self.pile_to_foundation(src)))
+            .collect();
+        for dst in 0..num_piles {
+            candidates.push(Move::WasteToPile(dst));
+        }
+        for src in 0..num_piles {
+            for index in 0..self.piles[src].len() {
+                for dst in 0..num_piles {
+                    candidates.push(Move::PileToPile { src, index, dst });
+                }
+            }
+        }
+        for slot in 0..self.foundations.len() {
+            for dst in 0..num_piles {
+                candidates.push(Move::FoundationToPile { slot, dst });
+            }
+        }
+        candidates.push(Move::StockToWaste);
+        candidates.push(Move::WasteToStock);
+
+        candidates.into_iter().filter(|mv| {
+            self.can_apply(*mv)
+        }).collect()
+    }
+
+    /// 移動できるかどうかを取得
+    pub fn can_apply(&self, mv: Move) -> bool {
+        match mv {
+            Move::StockToWaste => !self.stock.is_empty(),
+            Move::WasteToStock => self.stock.is_empty() && !self.waste.is_empty() && self.can_recycle(),
+            Move::WasteToPile(dst) => {
+                match self.waste.last() {
+                    Some(card) => dst < self.piles.len() && can_stack_pile(top_of(&self.piles[dst]), card.suit, card.number),
+                    None => false,
+                }
+            }
+            Move::WasteToFoundation(slot) => {
+                match self.waste.last() {
+                    Some(card) => self.can_stack_slot(slot, card),
+                    None => false,
+                }
+            }
+            Move::PileToPile { src, index, dst } => {
+                if src >= self.piles.len() || dst >= self.piles.len() || src == dst {
+                    return false;
+                }
+                match self.piles[src].get(index) {
+                    Some(card) => !card.facedown
+                        && can_stack_pile(top_of(&self.piles[dst]), card.suit, card.number),
+                    None => false,
+                }
+            }
+            Move::PileToFoundation { src, slot } => {
+                match self.piles.get(src).and_then(|pile| pile.last()) {
+                    Some(card) => !card.facedown && self.can_stack_slot(slot, card),
+                    None => false,
+                }
+            }
+            Move::FoundationToPile { slot, dst } => {
+                match self.foundations.get(slot).and_then(|foundation| foundation.last()) {
+                    Some(card) => dst < self.piles.len() && can_stack_pile(top_of(&self.piles[dst]), card.suit, card.number),
+                    None => false,
+                }
+            }
+        }
+    }
+
+    /// 移動を適用
+    pub fn apply(&mut self, mv: Move) -> Option<AppliedMove> {
+        if !self.can_apply(mv) {
+            return None;
+        }
+
+        let mut applied = AppliedMove {
+            mv,
+            num_cards: 1,
+            faceup: false,
+        };
+        match mv {
+            Move::StockToWaste => {
+                let num = (self.num_turn_to_waste as usize).clamp(1, self.stock.len());
+                for _ in 0..num {
+                    if let Some(mut card) = self.stock.pop() {
+                        card.facedown = false;
+                        self.waste.push(card);
+                    }
+                }
+                applied.num_cards = num;
+            }
+            Move::WasteToStock => {
+                self.num_recycle += 1;
+                applied.num_cards = self.waste.len();
+                while let Some(mut card) = self.waste.pop() {
+                    card.facedown = true;
+                    self.stock.push(card);
+                }
+            }
+            Move::WasteToPile(dst) => {
+                if let Some(card) = self.waste.pop() {
+                    self.piles[dst].push(card);
+                }
+            }
+            Move::WasteToFoundation(slot) => {
+                if let Some(card) = self.waste.pop() {
+                    self.foundations[slot].push(card);
+                }
+            }
+            Move::PileToPile { src, index, dst } => {
+                let cards = self.piles[src].split_off(index);
+                applied.num_cards = cards.len();
+                self.piles[dst].extend(cards);
+                applied.faceup = self.faceup_pile(src);
+            }
+            Move::PileToFoundation { src, slot } => {
+                if let Some(card) = self.piles[src].pop() {
+                    self.foundations[slot].push(card);
+                }
+                applied.faceup = self.faceup_pile(src);
+            }
+            Move::FoundationToPile { slot, dst } => {
+                if let Some(card) = self.foundations[slot].pop() {
+                    self.piles[dst].push(card);
+                }
+            }
+        }
+        Some(applied)
+    }
+
+    /// 適用した移動を元に戻す
+    pub fn revert(&mut self, applied: &AppliedMove) {
+        match applied.mv {
+            Move::StockToWaste => {
+                for _ in 0..applied.num_cards {
+                    if let Some(mut card) = self.waste.pop() {
+                        card.facedown = true;
+                        self.stock.push(card);
+                    }
+                }
+            }
+            Move::WasteToStock => {
+                self.num_recycle -= 1;
+                while let Some(mut card) = self.stock.pop() {
+                    card.facedown = false;
+                    self.waste.push(card);
+                }
+            }
+            Move::WasteToPile(dst) => {
+                if let Some(card) = self.piles[dst].pop() {
+                    self.waste.push(card);
+                }
+            }
+            Move::WasteToFoundation(slot) => {
+                if let Some(card) = self.foundations[slot].pop() {
+                    self.waste.push(card);
+                }
+            }
+            Move::PileToPile { src, dst, .. } => {
+                self.facedown_pile(src, applied.faceup);
+                let index = self.piles[dst].len() - applied.num_cards;
+                let cards = self.piles[dst].split_off(index);
+                self.piles[src].extend(cards);
+            }
+            Move::PileToFoundation { src, slot } => {
+                self.facedown_pile(src, applied.faceup);
+                if let Some(card) = self.foundations[slot].pop() {
+                    self.piles[src].push(card);
+                }
+            }
+            Move::FoundationToPile { slot, dst } => {
+                if let Some(card) = self.piles[dst].pop() {
+                    self.foundations[slot].push(card);
+                }
+            }
+        }
+    }
+
+    /// すべてのカードが組札に置かれたかどうかを取得
+    pub fn is_won(&self) -> bool {
+        self.stock.is_empty()
+            && self.waste.is_empty()
+            && self.piles.iter().all(|pile| pile.is_empty())
+    }
+
+    /// 山札に戻せるかどうかを取得
+    pub fn can_recycle(&self) -> bool {
+        self.max_recycle.is_none_or(|max| self.num_recycle < max)
+    }
+
+    /// 手詰まりかどうかを取得（山札を戻せず、状況が進む移動がない）
+    pub fn is_dead_end(&self) -> bool {
+        if self.is_won() {
+            return false;
+        }
+
+        let mut board = self.clone();
+        !self.legal_moves().into_iter().any(|mv| {
+            match mv {
+                Move::PileToPile { src, index, dst } => {
+                    if index == 0 {
+                        return !self.piles[dst].is_empty();
+                    }
+                    if self.piles[src][index - 1].facedown {
+                        return true;
+                    }
+                    board.is_useful_move(mv, src)
+                }
+                Move::FoundationToPile { dst, .. } => board.is_useful_move(mv, dst),
+                _ => true,
+            }
+        })
+    }
+
+    /// 移動したあとに山札か場札のカードを動かせるかどうかを取得
+    fn is_useful_move(&mut self, mv: Move, index: usize) -> bool {
+        let Some(applied) = self.apply(mv) else {
+            return false;
+        };
+        let useful = self.pile_to_foundation(index).is_some()
+            || (0..self.piles.len()).any(|dst| self.can_apply(Move::WasteToPile(dst)));
+        self.revert(&applied);
+        useful
+    }
+
+    /// ゲームクリア判定（残りのカードをすべて組札に移動できるか）
+    pub fn is_game_clear(&self) -> bool {
+        // 裏の場札がある or 山札が残っている
+        if !self.stock.is_empty() {
+            return false;
+        }
+        if self.piles.iter().flatten().any(|card| card.facedown) {
+            return false;
+        }
+
+        // 山札からめくったカードが1枚以下なら順に組札に移せる
+        if self.waste.len() <= 1 {
+            return true;
+        }
+        // 組札に移すだけで終わるか確認（山札に戻せる回数の制限も守る）
+        let mut board = self.clone();
+        let mut progress = true;
+        while !board.is_won() {
+            let foundation_move = board.waste_to_foundation()
+                .or_else(|| (0..board.piles.len()).find_map(|src| board.pile_to_foundation(src)));
+            if let Some(mv) = foundation_move {
+                board.apply(mv);
+                progress = true;
+            } else if !board.stock.is_empty() {
+                board.apply(Move::StockToWaste);
+            } else if progress && board.can_apply(Move::WasteToStock) {
+                board.apply(Move::WasteToStock);
+                progress = false;
+            } else {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// 組札に重ねられるかどうかを取得
+    pub fn can_stack_foundation(&self, card: &BoardCard) -> bool {
+        self.foundation_slot(card).is_some()
+    }
+
+    /// カードを重ねられる最初の組札の置き場を取得
+    pub fn foundation_slot(&self, card: &BoardCard) -> Option<usize> {
+        (0..self.foundations.len()).find(|slot| self.can_stack_slot(*slot, card))
+    }
+
+    /// 山札からめくったカードを組札に移す移動を取得
+    pub fn waste_to_foundation(&self) -> Option<Move> {
+        let slot = self.foundation_slot(self.waste.last()?)?;
+        Some(Move::WasteToFoundation(slot))
+    }
+
+    /// 場札の一番上のカードを組札に移す移動を取得
+    pub fn pile_to_foundation(&self, src: usize) -> Option<Move> {
+        let card = self.piles.get(src)?.last()?;
+        let mv = Move::PileToFoundation { src, slot: self.foundation_slot(card)? };
+        self.can_apply(mv).then_some(mv)
+    }
+
+    /// 組札の置き場に重ねられるかどうかを取得
+    fn can_stack_slot(&self, slot: usize, card: &BoardCard) -> bool {
+        self.foundations.get(slot).is_some_and(|foundation| {
+            foundation_suit(slot) == card.suit
+                && can_stack_foundation(top_of(foundation), card.suit, card.number, 1)
+        })
+    }
+
+    /// 場札か組札のどこかに置けるかどうかを取得
+    pub fn can_stack_anywhere(&self, card: &BoardCard) -> bool {
+        self.can_stack_foundation(card) || self.piles.iter().any(|pile| {
+            can_stack_pile(top_of(pile), card.suit, card.number)
+        })
+    }
+
+    /// 場札の一番上の裏向きカードをめくる
+    fn faceup_pile(&mut self, index: usize) -> bool {
+        if let Some(card) = self.piles[index].last_mut() {
+            if card.facedown {
+                card.facedown = false;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// めくった場札を裏向きに戻す
+    fn facedown_pile(&mut self, index: usize, faceup: bool) {
+        if faceup {
+            if let Some(card) = self.piles[index].last_mut() {
+                card.facedown = true;
+            }
+        }
+    }
+}
+
+/// 組札の置き場のスートを取得（2組のときは同じスートの置き場が2つある）
+pub fn foundation_suit(slot: usize) -> CardSuit {
+    CardSuit::ALL[slot % NUM_SUIT]
+}
+
+/// 場札に重ねられるかどうかを判定
+pub fn can_stack_pile(top: Option<(CardSuit, u32)>, suit: CardSuit, number: u32) -> bool {
+    match top {
+        Some((top_suit, top_number)) => top_suit.is_red() != suit.is_red() && number + 1 == top_number,
+        None => number == 13,
+    }
+}
+
+/// 組札に重ねられるかどうかを判定（最初は base の数字、K の次は A に戻り、base の手前で揃う）
+pub fn can_stack_foundation(top: Option<(CardSuit, u32)>, suit: CardSuit, number: u32, base: u32) -> bool {
+    match top {
+        Some((top_suit, top_number)) => top_suit == suit && number == next_rank(top_number) && number != base,
+        None => number == base,
+    }
+}
+
+/// 1つ大きい数字を取得（K の次は A）
+pub fn next_rank(number: u32) -> u32 {
+    number % 13 + 1
+}
+
+/// 配布番号からシャッフルしたカードの並びを取得
+pub fn shuffled_deck(seed: u64) -> Vec<(CardSuit, u32)> {
+    let mut deck = Vec::new();
+    for suit in CardSuit::ALL {
+        for number in 1..=13 {
+            deck.push((suit, number));
+        }
+    }
+
+    shuffle_deck(&mut deck, seed);
+    deck
+}
+
+/// 配布番号に応じてカードの並びをシャッフル
+pub fn shuffle_deck(deck: &mut [(CardSuit, u32)], seed: u64) {
+    // どの環境でも同じ並びになるよう、u32 の範囲で乱数を取得する
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    for i in (1..deck.len()).rev() {
+        let j = rng.gen_range(0..=i as u32) as usize;
+        deck.swap(i, j);
+    }
+}
+
+/// カード列の一番上のスートと数字を取得
+fn top_of(cards: &[BoardCard]) -> Option<(CardSuit, u32)> {
+    cards.last().map(|card| (card.suit, card.number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: u64 = 12345;
+
+    fn card(id: usize, suit: CardSuit, number: u32, facedown: bool) -> BoardCard {
+        BoardCard {
+            id,
+            suit,
+            number,
+            facedown,
+        }
+    }
+
+    /// 移動して元に戻すと同じ盤面になることを確認
+    fn assert_revertible(board: &Board, mv: Move) {
+        let mut moved = board.clone();
+        let applied = moved.apply(mv).unwrap_or_else(|| panic!("{:?} should be legal", mv));
+        assert_ne!(&moved, board, "{:?} should change the board", mv);
+        moved.revert(&applied);
+        assert_eq!(&moved, board, "{:?} should be reverted", mv);
+    }
+
+    #[test]
+    fn apply_and_revert_stock_moves() {
+        // 3枚ずつめくる
+        let board = Board::deal(SEED, 3);
+        assert_revertible(&board, Move::StockToWaste);
+        let mut moved = board.clone();
+        moved.apply(Move::StockToWaste);
+        assert_eq!(moved.waste.len(), 3);
+        assert!(moved.waste.iter().all(|card| !card.facedown));
+        assert_eq!(moved.waste.last(), board.stock.get(board.stock.len() - 3).map(|card| BoardCard {
+            facedown: false,
+            ..*card
+        }).as_ref());
+
+        // 残りが3枚未満のときは残りだけめくる
+        let mut board = Board::new(3);
+        board.stock = vec![card(0, CardSuit::Heart, 5, true), card(1, CardSuit::Club, 9, true)];
+        assert_revertible(&board, Move::StockToWaste);
+
+        // 山札に戻す
+        let mut board = Board::new(3);
+        board.max_recycle = Some(1);
+        board.waste = vec![
+            card(0, CardSuit::Heart, 5, false),
+            card(1, CardSuit::Club, 9, false),
+            card(2, CardSuit::Spade, 12, false),
+        ];
+        assert_revertible(&board, Move::WasteToStock);
+        let mut moved = board.clone();
+        moved.apply(Move::WasteToStock);
+        assert_eq!(moved.num_recycle, 1);
+        assert_eq!(moved.stock.last().map(|card| card.id), Some(0));
+        assert!(!moved.can_apply(Move::WasteToStock));
+    }
+
+    #[test]
+    fn apply_and_revert_card_moves() {
+        let mut board = Board::new(1);
+        board.waste = vec![card(0, CardSuit::Club, 4, false), card(1, CardSuit::Heart, 12, false)];
+        board.piles[0] = vec![card(2, CardSuit::Spade, 13, false)];
+        board.piles[1] = vec![card(3, CardSuit::Diamond, 9, true), card(4, CardSuit::Heart, 7, false), card(5, CardSuit::Club, 6, false)];
+        board.piles[2] = vec![card(6, CardSuit::Spade, 8, false)];
+        board.piles[3] = vec![card(7, CardSuit::Club, 10, true), card(8, CardSuit::Spade, 2, false)];
+        board.piles[4] = vec![card(9, CardSuit::Heart, 3, false)];
+        board.piles[5] = vec![card(14, CardSuit::Diamond, 7, false)];
+        board.foundations[CardSuit::Spade as usize] = vec![card(10, CardSuit::Spade, 1, false)];
+        board.foundations[CardSuit::Club as usize] = vec![
+            card(11, CardSuit::Club, 1, false),
+            card(12, CardSuit::Club, 2, false),
+        ];
+
+        assert_revertible(&board, Move::WasteToPile(0));
+        assert_revertible(&board, Move::PileToPile { src: 1, index: 1, dst: 2 });
+        assert_revertible(&board, Move::PileToPile { src: 1, index: 2, dst: 5 });
+        assert_revertible(&board, Move::PileToFoundation { src: 3, slot: CardSuit::Spade as usize });
+        assert_revertible(&board, Move::FoundationToPile { slot: CardSuit::Club as usize, dst: 4 });
+
+        // 裏向きのカードが一番上になったら表にする
+        let mut moved = board.clone();
+        let applied = moved.apply(Move::PileToPile { src: 1, index: 1, dst: 2 });
+        assert!(applied.is_some_and(|applied| applied.faceup && applied.num_cards == 2));
+        assert!(!moved.piles[1][0].facedown);
+
+        let mut board = board.clone();
+        board.waste.push(card(13, CardSuit::Spade, 2, false));
+        assert_revertible(&board, Move::WasteToFoundation(CardSuit::Spade as usize));
+    }
+
+    #[test]
+    fn apply_rejects_illegal_moves() {
+        let mut board = Board::deal(SEED, 1);
+        let before = board.clone();
+        assert!(board.apply(Move::WasteToStock).is_none());
+        assert!(board.apply(Move::WasteToFoundation(0)).is_none());
+        assert!(board.apply(Move::PileToPile { src: 6, index: 0, dst: 0 }).is_none());
+        assert!(board.apply(Move::PileToPile { src: 0, index: 0, dst: 0 }).is_none());
+        assert_eq!(board, before);
+    }
+
+    #[test]
+    fn two_foundations_per_suit() {
+        // 2組のときは同じスートの置き場が2つあり、空いていない方から重ねる
+        let mut board = Board::with_layout(9, 8, 1);
+        board.piles[0] = vec![card(0, CardSuit::Spade, 2, false), card(1, CardSuit::Spade, 1, false)];
+        board.waste = vec![card(2, CardSuit::Spade, 1, false)];
+        assert_eq!(board.waste_to_foundation(), Some(Move::WasteToFoundation(CardSuit::Spade as usize)));
+        assert_revertible(&board, Move::WasteToFoundation(CardSuit::Spade as usize));
+
+        board.apply(Move::WasteToFoundation(CardSuit::Spade as usize));
+        assert_eq!(board.pile_to_foundation(0), Some(Move::PileToFoundation { src: 0, slot: CardSuit::Spade as usize + NUM_SUIT }));
+        assert!(!board.can_apply(Move::PileToFoundation { src: 0, slot: CardSuit::Heart as usize }));
+
+        board.apply(Move::PileToFoundation { src: 0, slot: CardSuit::Spade as usize + NUM_SUIT });
+        assert_eq!(board.pile_to_foundation(0), Some(Move::PileToFoundation { src: 0, slot: CardSuit::Spade as usize }));
+        assert_revertible(&board, Move::PileToFoundation { src: 0, slot: CardSuit::Spade as usize });
+    }
+
+    #[test]
+    fn deal_lays_out_piles_and_stock() {
+        let board = Board::deal(SEED, 1);
+        assert_eq!(board, Board::deal(SEED, 1));
+        assert_ne!(board, Board::deal(SEED + 1, 1));
+        for (index, pile) in board.piles.iter().enumerate() {
+            assert_eq!(pile.len(), index + 1);
+            assert!(pile.iter().rev().skip(1).all(|card| card.facedown));
+            assert!(pile.last().is_some_and(|card| !card.facedown));
+        }
+        assert_eq!(board.stock.len(), 24);
+        assert!(board.waste.is_empty());
+    }
+
+    #[test]
+    fn legal_moves_on_dealt_board() {
+        let board = Board::deal(SEED, 1);
+        let moves = board.legal_moves();
+        assert!(moves.iter().all(|mv| board.can_apply(*mv)));
+        assert!(moves.contains(&Move::StockToWaste));
+
+        // 配った直後は一番上のカードを動かす移動と山札をめくる移動だけ
+        let tops: Vec<BoardCard> = board.piles.iter().map(|pile| *pile.last().unwrap()).collect();
+        let mut expected = Vec::new();
+        for (src, top) in tops.iter().enumerate() {
+            if top.number == 1 {
+                expected.push(Move::PileToFoundation { src, slot: top.suit as usize });
+            }
+        }
+        for (src, top) in tops.iter().enumerate() {
+            for (dst, dst_top) in tops.iter().enumerate() {
+                if src != dst && can_stack_pile(Some((dst_top.suit, dst_top.number)), top.suit, top.number) {
+                    expected.push(Move::PileToPile { src, index: src, dst });
+                }
+            }
+        }
+        expected.push(Move::StockToWaste);
+        assert_eq!(moves, expected);
+    }
+
+    #[test]
+    fn game_clear_needs_faceup_piles_and_empty_stock() {
+        let mut board = Board::new(1);
+        board.piles[0] = vec![card(0, CardSuit::Heart, 2, false), card(1, CardSuit::Spade, 1, false)];
+        board.foundations[CardSuit::Heart as usize] = vec![card(2, CardSuit::Heart, 1, false)];
+        assert!(board.is_game_clear());
+
+        let mut facedown = board.clone();
+        facedown.piles[0][0].facedown = true;
+        assert!(!facedown.is_game_clear());
+
+        let mut stock = board.clone();
+        stock.stock.push(card(3, CardSuit::Club, 1, true));
+        assert!(!stock.is_game_clear());
+    }
+
+    #[test]
+    fn game_clear_respects_stock_pass_limit() {
+        // A♠ の上に 2♠ が重なった山札から引いたカードは、山札に戻せないと組札に移せない
+        let mut board = Board::new(1);
+        board.waste = vec![card(0, CardSuit::Spade, 1, false), card(1, CardSuit::Spade, 2, false)];
+        board.max_recycle = Some(1);
+        assert!(board.is_game_clear());
+
+        board.num_recycle = 1;
+        assert!(!board.is_game_clear());
+
+        board.max_recycle = None;
+        assert!(board.is_game_clear());
+
+        // 3枚ずつめくるときは戻したあとの並びで判定する
+        let mut board = Board::new(3);
+        board.waste = vec![
+            card(0, CardSuit::Spade, 2, false),
+            card(1, CardSuit::Spade, 1, false),
+            card(2, CardSuit::Spade, 3, false),
+        ];
+        board.max_recycle = Some(1);
+        assert!(!board.is_game_clear());
+    }
+
+    #[test]
+    fn dead_end_when_nothing_moves_and_stock_cannot_recycle() {
+        let mut board = Board::new(1);
+        board.piles[0] = vec![card(0, CardSuit::Spade, 13, false)];
+        board.waste = vec![card(1, CardSuit::Heart, 5, false), card(2, CardSuit::Club, 9, false)];
+        board.max_recycle = Some(1);
+        board.num_recycle = 1;
+        assert!(board.is_dead_end());
+
+        // 山札に戻せるなら手詰まりではない
+        board.num_recycle = 0;
+        assert!(!board.is_dead_end());
+
+        // 組札に移せるカードがあれば手詰まりではない
+        board.num_recycle = 1;
+        board.waste.push(card(3, CardSuit::Diamond, 1, false));
+        assert!(!board.is_dead_end());
+
+        assert!(!Board::new(1).is_dead_end());
+        assert!(!Board::deal(SEED, 1).is_dead_end());
+    }
+}
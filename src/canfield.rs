@@ -0,0 +1,28 @@
+use crate::board::{next_rank, CardSuit};
+
+pub const NUM_CANFIELD_PILES: usize = 4;
+pub const NUM_RESERVE_CARDS: usize = 13;
+pub const NUM_CANFIELD_TURN: u32 = 3;
+
+/// 場札に重ねられるかどうかを判定（色違いで1つ小さい数字、A の上には K を置ける）
+pub fn can_stack_pile(top: Option<(CardSuit, u32)>, suit: CardSuit, number: u32) -> bool {
+    match top {
+        Some((top_suit, top_number)) => top_suit.is_red() != suit.is_red() && next_rank(number) == top_number,
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stack_alternating_colors_and_wrap_king_onto_ace() {
+        assert!(can_stack_pile(None, CardSuit::Heart, 9));
+        assert!(can_stack_pile(Some((CardSuit::Heart, 7)), CardSuit::Club, 6));
+        assert!(can_stack_pile(Some((CardSuit::Heart, 1)), CardSuit::Spade, 13));
+        assert!(!can_stack_pile(Some((CardSuit::Diamond, 1)), CardSuit::Heart, 13));
+        assert!(!can_stack_pile(Some((CardSuit::Heart, 7)), CardSuit::Spade, 5));
+        assert!(!can_stack_pile(Some((CardSuit::Heart, 7)), CardSuit::Diamond, 6));
+    }
+}
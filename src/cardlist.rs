@@ -1,9 +1,36 @@
+use std::collections::HashMap;
 use bevy::prelude::{Entity, Resource};
-use crate::game::{CardInfo, CardSuit, CardType};
+use crate::{
+    board::{self, foundation_suit, Board, BoardCard, CardSuit, Move, NUM_SUIT},
+    canfield::{self, NUM_CANFIELD_PILES},
+    freecell::{self, NUM_FREECELL_PILES, NUM_FREE_CELLS},
+    game::{CardInfo, CardType, MoveStep},
+    peaks::{self, NUM_GOLF_PILES, NUM_PEAKS_CARDS},
+    pyramid::{self, NUM_PYRAMID_CARDS},
+    resources::StockRecycle,
+    spider::{self, NUM_SPIDER_PILES},
+};
 
 #[derive(Resource)]
 pub struct CardList(pub Vec<CardInfo>);
 
+/// 移動元と移動先の置き場から手の種類を取得
+fn move_step(src: CardType, dst: CardType) -> Option<MoveStep> {
+    match (src, dst) {
+        (CardType::Waste, CardType::Pile(_)) => Some(MoveStep::WasteToPile),
+        (CardType::Waste, CardType::Foundation(_)) => Some(MoveStep::WasteToFoundation),
+        (CardType::Pile(_), CardType::Pile(_)) => Some(MoveStep::PileToPile),
+        (CardType::Pile(_), CardType::Foundation(_)) => Some(MoveStep::PileToFoundation),
+        (CardType::Pile(_), CardType::FreeCell(_)) => Some(MoveStep::PileToFreeCell),
+        (CardType::FreeCell(_), CardType::Pile(_)) => Some(MoveStep::FreeCellToPile),
+        (CardType::FreeCell(_), CardType::Foundation(_)) => Some(MoveStep::FreeCellToFoundation),
+        (CardType::Foundation(_), CardType::Pile(_)) => Some(MoveStep::FoundationToPile),
+        (CardType::Reserve, CardType::Pile(_)) => Some(MoveStep::ReserveToPile),
+        (CardType::Reserve, CardType::Foundation(_)) => Some(MoveStep::ReserveToFoundation),
+        (_, _) => None,
+    }
+}
+
 impl CardList {
     /// カードを取得
     pub fn get(&self, entity: Entity) -> Option<&CardInfo> {
@@ -31,7 +58,23 @@ impl CardList {
         let num = num.clamp(0, num);
         result.truncate(num as usize);
 
-        if result.len() > 0 {
+        if !result.is_empty() {
+            return Some(result);
+        }
+        None
+    }
+
+    /// 山札からめくったカードを取得
+    pub fn get_waste_cards(&self) -> Option<Vec<&CardInfo>> {
+        let mut result: Vec<&CardInfo> = self.0.iter().filter(|card| {
+            card.card_type == CardType::Waste
+        }).collect();
+
+        result.sort_by(|a, b| {
+            a.order.cmp(&b.order)
+        });
+
+        if !result.is_empty() {
             return Some(result);
         }
         None
@@ -46,7 +89,11 @@ impl CardList {
             result.push(&mut *card);
         }
 
-        if result.len() > 0 {
+        result.sort_by(|a, b| {
+            a.order.cmp(&b.order)
+        });
+
+        if !result.is_empty() {
             return Some(result);
         }
         None
@@ -65,7 +112,7 @@ impl CardList {
             a.order.cmp(&b.order)
         });
 
-        if result.len() > 0 {
+        if !result.is_empty() {
             return Some(result);
         }
         None
@@ -84,17 +131,17 @@ impl CardList {
             a.order.cmp(&b.order)
         });
 
-        if result.len() > 0 {
+        if !result.is_empty() {
             return Some(result);
         }
         None
     }
     
     /// 組札カードを取得
-    pub fn get_foundation_cards(&self, suit: CardSuit) -> Option<Vec<&CardInfo>> {
+    pub fn get_foundation_cards(&self, slot: u32) -> Option<Vec<&CardInfo>> {
         let mut result = Vec::new();
         for card in self.0.iter().filter(|card| {
-            card.card_type == CardType::Foundation(suit)
+            card.card_type == CardType::Foundation(slot)
         }) {
             result.push(card);
         }
@@ -103,12 +150,38 @@ impl CardList {
             a.order.cmp(&b.order)
         });
 
-        if result.len() > 0 {
+        if !result.is_empty() {
             return Some(result);
         }
         None
     }
 
+    /// 予備札を取得（キャンフィールド）
+    pub fn get_reserve_cards_mut(&mut self) -> Option<Vec<&mut CardInfo>> {
+        let mut result = Vec::new();
+        for card in self.0.iter_mut().filter(|card| {
+            card.card_type == CardType::Reserve
+        }) {
+            result.push(&mut *card);
+        }
+
+        result.sort_by(|a, b| {
+            a.order.cmp(&b.order)
+        });
+
+        if !result.is_empty() {
+            return Some(result);
+        }
+        None
+    }
+
+    /// フリーセルのカードを取得
+    pub fn get_free_cell_card(&self, index: u32) -> Option<&CardInfo> {
+        self.0.iter().find(|card| {
+            card.card_type == CardType::FreeCell(index)
+        })
+    }
+
     /// 重ねられたカードを取得
     pub fn get_connected_cards(&self, target: Entity) -> Option<Vec<&CardInfo>> {
         let mut result = Vec::new();
@@ -120,7 +193,7 @@ impl CardList {
             }
         }
 
-        if result.len() > 0 {
+        if !result.is_empty() {
             return Some(result);
         } 
         None
@@ -144,7 +217,7 @@ impl CardList {
             }
         }
 
-        if result.len() > 0 {
+        if !result.is_empty() {
             return Some(result);
         } 
         None
@@ -152,44 +225,354 @@ impl CardList {
 
     /// 場札に重ねられるかどうかを取得
     pub fn can_stack_pile(&self, index: u32, target: &CardInfo) -> Option<i32> {
-        let mut result = None;
-        if let Some(cards) = self.get_pile_cards(index as u32) {
-            if let Some(card) = cards.last() {
-                if (target.card_suit == CardSuit::Heart || target.card_suit == CardSuit::Diamond)
-                && (card.card_suit == CardSuit::Club || card.card_suit == CardSuit::Spade) {
-                    if target.card_number == card.card_number - 1 {
-                        result = Some(cards.len() as i32);
-                    }
-                } else if (target.card_suit == CardSuit::Club || target.card_suit == CardSuit::Spade)
-                && (card.card_suit == CardSuit::Heart || card.card_suit == CardSuit::Diamond) {
-                    if target.card_number == card.card_number - 1 {
-                        result = Some(cards.len() as i32);
-                    }
-                }
+        let cards = self.get_pile_cards(index);
+        let top = cards.as_ref().and_then(|cards| {
+            cards.last().map(|card| (card.card_suit, card.card_number))
+        });
+
+        if board::can_stack_pile(top, target.card_suit, target.card_number) {
+            return Some(cards.map_or(0, |cards| cards.len() as i32));
+        }
+        None
+    }
+
+    /// 組札に重ねられるかどうかを取得
+    pub fn can_stack_foundation(&self, slot: u32, target: &CardInfo, base: u32) -> bool {
+        let top = self.get_foundation_cards(slot).and_then(|cards| {
+            cards.last().map(|card| (card.card_suit, card.card_number))
+        });
+
+        foundation_suit(slot as usize) == target.card_suit
+            && board::can_stack_foundation(top, target.card_suit, target.card_number, base)
+    }
+
+    /// 組札に移しても困らないかどうかを取得（rank は最初の数字を1とした順番。反対の色の1つ前の数字がすべて組札にある）
+    pub fn is_safe_foundation(&self, target: &CardInfo, rank: i32, num_foundations: usize) -> bool {
+        rank <= 2 || (0..num_foundations as u32).filter(|slot| {
+            foundation_suit(*slot as usize).is_red() != target.card_suit.is_red()
+        }).all(|slot| {
+            self.get_foundation_cards(slot).map_or(0, |cards| cards.len() as i32) >= rank - 1
+        })
+    }
+
+    /// 同じカードが何組目のものかを振り直す
+    pub fn assign_decks(&mut self) {
+        let num_decks = (self.0.len() / (NUM_SUIT * 13)).max(1) as u32;
+        let mut counts = HashMap::new();
+        for card in self.0.iter_mut() {
+            let count = counts.entry((card.card_suit, card.card_number)).or_insert(0);
+            card.deck = *count % num_decks;
+            *count += 1;
+        }
+    }
+
+    /// 重ねられたカードと合わせて同じスートの並びになっているかどうかを取得（スパイダー）
+    pub fn is_spider_run(&self, target: &CardInfo) -> bool {
+        let mut cards = self.get_connected_cards(target.entity).unwrap_or_default();
+        cards.sort_by(|a, b| {
+            a.order.cmp(&b.order)
+        });
+
+        let run: Vec<(CardSuit, u32)> = std::iter::once(target).chain(cards).map(|card| {
+            (card.card_suit, card.card_number)
+        }).collect();
+        !target.facedown && spider::is_run(&run)
+    }
+
+    /// 場札に重ねられるかどうかを取得（スパイダー）
+    pub fn can_stack_spider_pile(&self, index: u32, target: &CardInfo) -> Option<i32> {
+        if target.card_type == CardType::Pile(index) || !self.is_spider_run(target) {
+            return None;
+        }
+
+        let cards = self.get_pile_cards(index);
+        let top = cards.as_ref().and_then(|cards| {
+            cards.last().map(|card| card.card_number)
+        });
+
+        if spider::can_stack_pile(top, target.card_number) {
+            return Some(cards.map_or(0, |cards| cards.len() as i32));
+        }
+        None
+    }
+
+    /// 重ねられたカードと合わせて色が交互の並びになっているかどうかを取得（フリーセル）
+    pub fn is_freecell_run(&self, target: &CardInfo) -> bool {
+        let mut cards = self.get_connected_cards(target.entity).unwrap_or_default();
+        cards.sort_by(|a, b| {
+            a.order.cmp(&b.order)
+        });
+
+        let run: Vec<(CardSuit, u32)> = std::iter::once(target).chain(cards).map(|card| {
+            (card.card_suit, card.card_number)
+        }).collect();
+        freecell::is_run(&run)
+    }
+
+    /// 場札に重ねられるかどうかを取得（フリーセル）
+    pub fn can_stack_freecell_pile(&self, index: u32, target: &CardInfo) -> Option<i32> {
+        if target.card_type == CardType::Pile(index) || !self.is_freecell_run(target) {
+            return None;
+        }
+
+        // 空いたフリーセルと移動先以外の空いた列の数で、まとめて動かせる枚数が決まる
+        let num_cards = 1 + self.get_connected_cards(target.entity).map_or(0, |cards| cards.len());
+        let num_empty_cells = (0..NUM_FREE_CELLS as u32).filter(|cell| {
+            self.get_free_cell_card(*cell).is_none()
+        }).count();
+        let num_empty_piles = (0..NUM_FREECELL_PILES as u32).filter(|pile| {
+            *pile != index && self.get_pile_cards(*pile).is_none()
+        }).count();
+        if num_cards > freecell::max_supermove(num_empty_cells, num_empty_piles) {
+            return None;
+        }
+
+        let cards = self.get_pile_cards(index);
+        let top = cards.as_ref().and_then(|cards| {
+            cards.last().map(|card| (card.card_suit, card.card_number))
+        });
+
+        if freecell::can_stack_pile(top, target.card_suit, target.card_number) {
+            return Some(cards.map_or(0, |cards| cards.len() as i32));
+        }
+        None
+    }
+
+    /// 場札に重ねられるかどうかを取得（キャンフィールド）
+    pub fn can_stack_canfield_pile(&self, index: u32, target: &CardInfo) -> Option<i32> {
+        if target.card_type == CardType::Pile(index) {
+            return None;
+        }
+
+        let cards = self.get_pile_cards(index);
+        let top = cards.as_ref().and_then(|cards| {
+            cards.last().map(|card| (card.card_suit, card.card_number))
+        });
+
+        if canfield::can_stack_pile(top, target.card_suit, target.card_number) {
+            return Some(cards.map_or(0, |cards| cards.len() as i32));
+        }
+        None
+    }
+
+    /// 場札の上に揃った列を取得（スパイダー）
+    pub fn get_complete_run(&self, index: u32) -> Option<Vec<Entity>> {
+        let cards = self.get_pile_cards(index)?;
+        let run = &cards[cards.len().checked_sub(spider::NUM_RUN_CARDS)?..];
+        let suit_and_num: Vec<(CardSuit, u32)> = run.iter().map(|card| {
+            (card.card_suit, card.card_number)
+        }).collect();
+
+        if run.iter().all(|card| !card.facedown) && spider::is_complete_run(&suit_and_num) {
+            return Some(run.iter().map(|card| card.entity).collect());
+        }
+        None
+    }
+
+    /// 場札の上に揃った列を組札に移し、列の手前のカードをめくる（スパイダー）
+    pub fn remove_complete_run(&mut self, index: u32) -> Vec<MoveStep> {
+        let Some(run) = self.get_complete_run(index) else {
+            return Vec::new();
+        };
+        let Some(suit) = run.first().and_then(|entity| self.get(*entity)).map(|card| card.card_suit) else {
+            return Vec::new();
+        };
+        let order = self.get_foundation_cards(suit as u32).map_or(0, |cards| cards.len() as i32);
+
+        // K が一番上に見えるように重ねる
+        for (offset, entity) in run.iter().rev().enumerate() {
+            if let Some(card) = self.get_mut(*entity) {
+                card.card_type = CardType::Foundation(suit as u32);
+                card.order = order + offset as i32;
+                card.clickable = false;
             }
-        } else {
-            if target.card_number == 13 {
-                result = Some(0);
+        }
+        std::iter::once(MoveStep::PileToFoundation).chain(self.faceup_pile(index)).collect()
+    }
+
+    /// 山札から各列に1枚ずつ表向きで配り、揃った列を組札に移す（スパイダー）
+    pub fn deal_spider_stock(&mut self) -> Vec<MoveStep> {
+        // 空いた列があるときは配れない
+        let num_pile_cards: Vec<i32> = (0..NUM_SPIDER_PILES).map(|index| {
+            self.get_pile_cards(index as u32).map_or(0, |cards| cards.len() as i32)
+        }).collect();
+        if num_pile_cards.contains(&0) {
+            return Vec::new();
+        }
+
+        let Some(turn_cards) = self.get_turn_cards_mut(NUM_SPIDER_PILES as u32) else {
+            return Vec::new();
+        };
+        for (index, card) in turn_cards.into_iter().enumerate() {
+            card.card_type = CardType::Pile(index as u32);
+            card.order = num_pile_cards[index];
+            card.clickable = true;
+            card.facedown = false;
+        }
+
+        let mut steps = vec![MoveStep::StockToPile];
+        for index in 0..NUM_SPIDER_PILES {
+            steps.extend(self.remove_complete_run(index as u32));
+        }
+        steps
+    }
+
+    /// 空いた列に予備札を補充して、予備札の一番上を表向きにする（キャンフィールド）
+    pub fn fill_canfield_piles(&mut self) -> Vec<MoveStep> {
+        let mut steps = Vec::new();
+        for index in 0..NUM_CANFIELD_PILES as u32 {
+            if self.get_pile_cards(index).is_some() {
+                continue;
             }
+            let Some(card) = self.get_reserve_cards_mut().and_then(|cards| cards.into_iter().last()) else {
+                break;
+            };
+
+            card.card_type = CardType::Pile(index);
+            card.order = 0;
+            card.clickable = true;
+            card.facedown = false;
+            steps.push(MoveStep::ReserveToPile);
         }
-        result
+
+        if let Some(card) = self.get_reserve_cards_mut().and_then(|cards| cards.into_iter().last()) {
+            card.clickable = true;
+            card.facedown = false;
+        }
+        steps
     }
 
-    /// 組札に重ねられるかどうかを取得
-    pub fn can_stack_foundation(&self, target: &CardInfo) -> bool {
-        let mut result = false;
-        if let Some(cards) = self.get_foundation_cards(target.card_suit) {
-            if let Some(card) = cards.last() {
-                if target.card_suit == card.card_suit && target.card_number == card.card_number + 1 {
-                    result = true;
-                }
+    /// 取り除いたカードを組札に移す（ピラミッド）
+    pub fn remove_pyramid_cards(&mut self, entities: &[Entity]) -> Vec<MoveStep> {
+        for entity in entities {
+            let Some(suit) = self.get(*entity).map(|card| card.card_suit) else {
+                continue;
+            };
+            let order = self.get_foundation_cards(suit as u32).map_or(0, |cards| cards.len() as i32);
+            if let Some(card) = self.get_mut(*entity) {
+                card.card_type = CardType::Foundation(suit as u32);
+                card.order = order;
+                card.clickable = false;
             }
-        } else {
-            if target.card_number == 1 {
-                result = true;
+        }
+
+        // 上に重なるカードがなくなったカードをクリックできるようにする
+        let occupied: Vec<bool> = (0..NUM_PYRAMID_CARDS).map(|index| {
+            self.0.iter().any(|card| card.card_type == CardType::Pyramid(index as u32))
+        }).collect();
+        for card in self.0.iter_mut() {
+            if let CardType::Pyramid(index) = card.card_type {
+                card.clickable = pyramid::is_exposed(index as usize, |index| occupied[index]);
             }
         }
-        result
+        vec![MoveStep::RemoveCards]
+    }
+
+    /// 捨て札の一番上と数字が1つ違う場のカードを捨て札に重ねる（トライピークス、ゴルフ）
+    pub fn play_peaks_card(&mut self, target: Entity, wrap_ranks: bool) -> Vec<MoveStep> {
+        let Some(top) = self.get_waste_cards().and_then(|cards| cards.last().map(|card| card.card_number)) else {
+            return Vec::new();
+        };
+        if !self.get(target).is_some_and(|card| peaks::can_play(top, card.card_number, wrap_ranks)) {
+            return Vec::new();
+        }
+
+        let order = self.get_waste_cards().map_or(0, |cards| cards.len() as i32);
+        if let Some(card) = self.get_mut(target) {
+            card.card_type = CardType::Waste;
+            card.order = order;
+            card.clickable = false;
+        }
+
+        // 上に重なるカードがなくなった場のカードを表向きにしてクリックできるようにする
+        let occupied: Vec<bool> = (0..NUM_PEAKS_CARDS).map(|index| {
+            self.0.iter().any(|card| card.card_type == CardType::Peak(index as u32))
+        }).collect();
+        let tops: Vec<Option<i32>> = (0..NUM_GOLF_PILES).map(|index| {
+            self.get_pile_cards(index as u32).and_then(|cards| cards.last().map(|card| card.order))
+        }).collect();
+        for card in self.0.iter_mut() {
+            let exposed = match card.card_type {
+                CardType::Peak(index) => peaks::is_exposed(index as usize, |index| occupied[index]),
+                CardType::Pile(index) => tops.get(index as usize) == Some(&Some(card.order)),
+                _ => continue,
+            };
+
+            card.clickable = exposed;
+            if exposed {
+                card.facedown = false;
+            }
+        }
+        vec![MoveStep::TableauToWaste]
+    }
+
+    /// カードを移動先に移す（場札の列からはつながったカードも一緒に移し、列の手前のカードをめくる）
+    /// 動かした手を返す（動かせない置き場のときは空）
+    pub fn move_cards(&mut self, target: Entity, dst: CardType, order: i32) -> Vec<MoveStep> {
+        let Some(card) = self.get(target) else {
+            return Vec::new();
+        };
+        let (src, src_order) = (card.card_type, card.order);
+        let Some(step) = move_step(src, dst) else {
+            return Vec::new();
+        };
+
+        for card in self.0.iter_mut() {
+            let connected = matches!(src, CardType::Pile(_)) && card.card_type == src && card.order > src_order;
+            if card.entity == target || connected {
+                card.order = order + card.order - src_order;
+                card.card_type = dst;
+            }
+        }
+
+        let mut steps = vec![step];
+        if let CardType::Pile(index) = src {
+            steps.extend(self.faceup_pile(index));
+        }
+        steps
+    }
+
+    /// 場札の列の手前のカードが裏向きならめくる
+    fn faceup_pile(&mut self, index: u32) -> Option<MoveStep> {
+        let last = self.get_pile_cards_mut(index)?.into_iter().last()?;
+        if !last.facedown {
+            return None;
+        }
+
+        last.clickable = true;
+        last.facedown = false;
+        Some(MoveStep::FaceupPile)
+    }
+
+    /// 山札を決められた枚数だけめくって、山札から引いたカードの上に重ねる
+    pub fn turn_stock(&mut self, num: u32) -> Vec<MoveStep> {
+        let num_waste_cards = self.get_waste_cards().map_or(0, |cards| cards.len());
+        let Some(turn_cards) = self.get_turn_cards_mut(num) else {
+            return Vec::new();
+        };
+
+        for (count, card) in turn_cards.into_iter().enumerate() {
+            card.card_type = CardType::Waste;
+            card.order = (num_waste_cards + count) as i32;
+            card.clickable = false;
+            card.facedown = false;
+        }
+        vec![MoveStep::StockToWaste]
+    }
+
+    /// 山札からめくったカードをすべて山札に戻す
+    pub fn return_waste_to_stock(&mut self) -> Vec<MoveStep> {
+        let Some(waste_cards) = self.get_waste_cards_mut() else {
+            return Vec::new();
+        };
+
+        for (count, card) in waste_cards.into_iter().enumerate() {
+            card.card_type = CardType::Stock;
+            card.order = count as i32;
+            card.clickable = true;
+            card.facedown = true;
+        }
+        vec![MoveStep::WasteToStock]
     }
 
     /// 場札の裏向きカードの枚数を取得
@@ -213,4 +596,150 @@ impl CardList {
         }
         result
     }
+
+    /// カードの置き場から盤面を作る（カードの id は CardList の並び順）
+    pub fn to_board(
+        &self,
+        num_piles: usize,
+        num_foundations: usize,
+        num_turn_to_waste: u32,
+        stock_recycle: &StockRecycle
+    ) -> Board {
+        let mut board = Board::with_layout(num_piles, num_foundations, num_turn_to_waste);
+        board.num_recycle = stock_recycle.count;
+        board.max_recycle = stock_recycle.limit;
+        let to_board_card = |card: &CardInfo| {
+            BoardCard {
+                id: self.0.iter().position(|c| c.entity == card.entity).unwrap_or_default(),
+                suit: card.card_suit,
+                number: card.card_number,
+                facedown: card.facedown,
+            }
+        };
+
+        // 山札は順番の小さいカードからめくるので、末尾が次にめくるカード
+        let mut stock: Vec<&CardInfo> = self.0.iter().filter(|card| {
+            card.card_type == CardType::Stock
+        }).collect();
+        stock.sort_by(|a, b| {
+            b.order.cmp(&a.order)
+        });
+        board.stock = stock.into_iter().map(to_board_card).collect();
+
+        board.waste = self.get_waste_cards().map_or_else(Vec::new, |cards| {
+            cards.into_iter().map(to_board_card).collect()
+        });
+
+        for (index, pile) in board.piles.iter_mut().enumerate() {
+            if let Some(cards) = self.get_pile_cards(index as u32) {
+                *pile = cards.into_iter().map(to_board_card).collect();
+            }
+        }
+
+        for (slot, foundation) in board.foundations.iter_mut().enumerate() {
+            if let Some(cards) = self.get_foundation_cards(slot as u32) {
+                *foundation = cards.into_iter().map(to_board_card).collect();
+            }
+        }
+
+        board
+    }
+
+    /// 盤面に合わせてカードの置き場を更新
+    pub fn sync_board(&mut self, board: &Board) {
+        let stock = board.stock.iter().rev().enumerate().map(|(order, card)| {
+            (card, CardType::Stock, order as i32, true)
+        });
+        let waste = board.waste.iter().enumerate().map(|(order, card)| {
+            (card, CardType::Waste, order as i32, order + 1 == board.waste.len())
+        });
+        let piles = board.piles.iter().enumerate().flat_map(|(index, pile)| {
+            pile.iter().enumerate().map(move |(order, card)| {
+                (card, CardType::Pile(index as u32), order as i32, !card.facedown)
+            })
+        });
+        // 組札の順番は最初の数字を1とする
+        let foundations = board.foundations.iter().enumerate().flat_map(|(slot, cards)| {
+            cards.iter().enumerate().map(move |(order, card)| {
+                (card, CardType::Foundation(slot as u32), order as i32 + 1, true)
+            })
+        });
+
+        for (board_card, card_type, order, clickable) in stock.chain(waste).chain(piles).chain(foundations) {
+            if let Some(card) = self.0.get_mut(board_card.id) {
+                card.card_type = card_type;
+                card.order = order;
+                card.facedown = board_card.facedown;
+                card.clickable = clickable;
+            }
+        }
+    }
+
+    /// カードを移動先に移す盤面の移動を取得
+    pub fn get_board_move(&self, target: Entity, dst: CardType) -> Option<Move> {
+        let card = self.get(target)?;
+        match (card.card_type, dst) {
+            (CardType::Waste, CardType::Pile(dst)) => Some(Move::WasteToPile(dst as usize)),
+            (CardType::Waste, CardType::Foundation(slot)) => Some(Move::WasteToFoundation(slot as usize)),
+            (CardType::Pile(src), CardType::Pile(dst)) => Some(Move::PileToPile {
+                src: src as usize,
+                index: card.order as usize,
+                dst: dst as usize,
+            }),
+            (CardType::Pile(src), CardType::Foundation(slot)) => Some(Move::PileToFoundation {
+                src: src as usize,
+                slot: slot as usize,
+            }),
+            (CardType::Foundation(slot), CardType::Pile(dst)) => Some(Move::FoundationToPile {
+                slot: slot as usize,
+                dst: dst as usize,
+            }),
+            (_, _) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::NUM_PILES;
+
+    /// 盤面のカードと同じ並びのカードの置き場を作る
+    fn card_list_from_board(board: &Board) -> CardList {
+        let mut cards: Vec<&BoardCard> = board.stock.iter()
+            .chain(board.waste.iter())
+            .chain(board.piles.iter().flatten())
+            .chain(board.foundations.iter().flatten())
+            .collect();
+        cards.sort_by_key(|card| card.id);
+        let mut card_list = CardList(cards.into_iter().map(|card| CardInfo {
+            entity: Entity::from_raw(card.id as u32),
+            card_suit: card.suit,
+            card_number: card.number,
+            ..Default::default()
+        }).collect());
+        card_list.sync_board(board);
+        card_list
+    }
+
+    #[test]
+    fn sync_board_round_trips_through_to_board() {
+        let stock_recycle = StockRecycle { count: 0, limit: Some(2) };
+        let mut board = Board::deal(12345, 3);
+        board.max_recycle = stock_recycle.limit;
+        let mut card_list = card_list_from_board(&board);
+        assert_eq!(card_list.to_board(NUM_PILES, NUM_SUIT, 3, &stock_recycle), board);
+
+        // 山札をめくったり、場札を動かしたりしても同じ盤面に戻る
+        for _ in 0..30 {
+            let moves = board.legal_moves();
+            let Some(mv) = moves.iter().copied().find(|mv| *mv != Move::StockToWaste).or(moves.first().copied()) else {
+                break;
+            };
+            board.apply(mv);
+            card_list.sync_board(&board);
+            let stock_recycle = StockRecycle { count: board.num_recycle, ..stock_recycle };
+            assert_eq!(card_list.to_board(NUM_PILES, NUM_SUIT, 3, &stock_recycle), board, "{:?} should be synced", mv);
+        }
+    }
 }
@@ -0,0 +1,56 @@
+use crate::board::CardSuit;
+
+pub const NUM_FREE_CELLS: usize = 4;
+pub const NUM_FREECELL_PILES: usize = 8;
+
+/// 場札に重ねられるかどうかを判定（空いた列にはどのカードでも置ける）
+pub fn can_stack_pile(top: Option<(CardSuit, u32)>, suit: CardSuit, number: u32) -> bool {
+    match top {
+        Some((top_suit, top_number)) => top_suit.is_red() != suit.is_red() && number + 1 == top_number,
+        None => true,
+    }
+}
+
+/// 色が交互で数字が1つずつ小さくなっているかどうかを判定
+pub fn is_run(cards: &[(CardSuit, u32)]) -> bool {
+    cards.windows(2).all(|pair| {
+        let ((suit, number), (next_suit, next_number)) = (pair[0], pair[1]);
+        suit.is_red() != next_suit.is_red() && number == next_number + 1
+    })
+}
+
+/// 一度に動かせる枚数を取得（空いたフリーセルと空いた列の数で決まる）
+pub fn max_supermove(num_empty_cells: usize, num_empty_piles: usize) -> usize {
+    (num_empty_cells + 1) << num_empty_piles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supermove_limit_grows_with_empty_cells_and_piles() {
+        assert_eq!(max_supermove(0, 0), 1);
+        assert_eq!(max_supermove(NUM_FREE_CELLS, 0), 5);
+        assert_eq!(max_supermove(0, 1), 2);
+        assert_eq!(max_supermove(3, 1), 8);
+        assert_eq!(max_supermove(NUM_FREE_CELLS, 2), 20);
+    }
+
+    #[test]
+    fn any_card_goes_on_an_empty_pile() {
+        assert!(can_stack_pile(None, CardSuit::Heart, 5));
+        assert!(can_stack_pile(Some((CardSuit::Spade, 6)), CardSuit::Heart, 5));
+        assert!(!can_stack_pile(Some((CardSuit::Diamond, 6)), CardSuit::Heart, 5));
+        assert!(!can_stack_pile(Some((CardSuit::Spade, 7)), CardSuit::Heart, 5));
+    }
+
+    #[test]
+    fn run_alternates_colors_downwards() {
+        assert!(is_run(&[]));
+        assert!(is_run(&[(CardSuit::Club, 9)]));
+        assert!(is_run(&[(CardSuit::Club, 9), (CardSuit::Heart, 8), (CardSuit::Spade, 7)]));
+        assert!(!is_run(&[(CardSuit::Club, 9), (CardSuit::Spade, 8)]));
+        assert!(!is_run(&[(CardSuit::Club, 9), (CardSuit::Heart, 7)]));
+    }
+}
@@ -2,110 +2,87 @@ use std::time::Duration;
 use bevy::{
     math::bounding::{Aabb2d, IntersectsVolume},
     prelude::*,
+    tasks::{block_on, poll_once, AsyncComputeTaskPool, Task},
     text::TextBounds,
     time::common_conditions::on_timer
 };
-use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::board::{foundation_suit, AppliedMove, Board, CardSuit, Move, NUM_SUIT};
+use crate::freecell::NUM_FREE_CELLS;
+use crate::history::{CardLocation, History, Snapshot};
+use crate::information::{Movement, Score};
 use crate::resources::{
-    GameDifficulty, GameFonts, GameTextures
+    CurrentOptions,
+    DealSeed,
+    DrawRule,
+    GameFonts,
+    GameKind,
+    GameOptions,
+    GameTextures,
+    ClickMode,
+    ScoringMode,
+    StockPasses,
+    StockRecycle,
 };
 use crate::cardlist::CardList;
+use crate::ruleset::{ClickAction, GameOption};
+use crate::savedata::SavedGame;
+use crate::solver::{self, DealAnalysis};
 
 const POSITION_STOCK: Vec2 = Vec2::new(-258., 63.);
 const POSITION_WASTE: Vec2 = Vec2::new(-258., 1.);
-const POSITION_PILES: [Vec2; NUM_PILES] = [
-    Vec2::new(-193., 62.),
-    Vec2::new(-129., 62.),
-    Vec2::new(-65., 62.),
-    Vec2::new(-1., 62.),
-    Vec2::new(63., 62.),
-    Vec2::new(127., 62.),
-    Vec2::new(191., 62.),
-];
 const POSITION_FOUNDATIONS: [Vec2; NUM_SUIT] = [
     Vec2::new(257., 62.),
     Vec2::new(257., 2.),
     Vec2::new(257., -58.),
     Vec2::new(257., -118.),
 ];
-const NUM_SUIT: usize = 4;
-const NUM_PILES: usize = 7;
-const MAX_WASTES: u32 = 3;
 
 const CARD_SIZE: Vec2 = Vec2::new(38., 52.);
 const OFFSET_WASTE_Y: f32 = 16.;
 const OFFSET_PILE_Y: f32 = 16.;
 const OFFSET_PILE_Y_MIN: f32 = 6.;
-const PILE_DROP_AREA: [Aabb2d; NUM_PILES] = [
-    Aabb2d {
-        min: Vec2::new(POSITION_PILES[0].x - CARD_SIZE.x / 2., -144.),
-        max: Vec2::new(POSITION_PILES[0].x + CARD_SIZE.x / 2., 88.),
-    },
-    Aabb2d {
-        min: Vec2::new(POSITION_PILES[1].x - CARD_SIZE.x / 2., -144.),
-        max: Vec2::new(POSITION_PILES[1].x + CARD_SIZE.x / 2., 88.),
-    },
-    Aabb2d {
-        min: Vec2::new(POSITION_PILES[2].x - CARD_SIZE.x / 2., -144.),
-        max: Vec2::new(POSITION_PILES[2].x + CARD_SIZE.x / 2., 88.),
-    },
-    Aabb2d {
-        min: Vec2::new(POSITION_PILES[3].x - CARD_SIZE.x / 2., -144.),
-        max: Vec2::new(POSITION_PILES[3].x + CARD_SIZE.x / 2., 88.),
-    },
-    Aabb2d {
-        min: Vec2::new(POSITION_PILES[4].x - CARD_SIZE.x / 2., -144.),
-        max: Vec2::new(POSITION_PILES[4].x + CARD_SIZE.x / 2., 88.),
-    },
-    Aabb2d {
-        min: Vec2::new(POSITION_PILES[5].x - CARD_SIZE.x / 2., -144.),
-        max: Vec2::new(POSITION_PILES[5].x + CARD_SIZE.x / 2., 88.),
-    },
-    Aabb2d {
-        min: Vec2::new(POSITION_PILES[6].x - CARD_SIZE.x / 2., -144.),
-        max: Vec2::new(POSITION_PILES[6].x + CARD_SIZE.x / 2., 88.),
-    },
-];
-const FOUNDATION_DROP_AREA: [Aabb2d; NUM_SUIT] = [
-    Aabb2d {
-        min: Vec2::new(POSITION_FOUNDATIONS[0].x - CARD_SIZE.x / 2., POSITION_FOUNDATIONS[0].y - CARD_SIZE.y / 2.),
-        max: Vec2::new(POSITION_FOUNDATIONS[0].x + CARD_SIZE.x / 2., POSITION_FOUNDATIONS[0].y + CARD_SIZE.y / 2.),
-    },
-    Aabb2d {
-        min: Vec2::new(POSITION_FOUNDATIONS[1].x - CARD_SIZE.x / 2., POSITION_FOUNDATIONS[1].y - CARD_SIZE.y / 2.),
-        max: Vec2::new(POSITION_FOUNDATIONS[1].x + CARD_SIZE.x / 2., POSITION_FOUNDATIONS[1].y + CARD_SIZE.y / 2.),
-    },
-    Aabb2d {
-        min: Vec2::new(POSITION_FOUNDATIONS[2].x - CARD_SIZE.x / 2., POSITION_FOUNDATIONS[2].y - CARD_SIZE.y / 2.),
-        max: Vec2::new(POSITION_FOUNDATIONS[2].x + CARD_SIZE.x / 2., POSITION_FOUNDATIONS[2].y + CARD_SIZE.y / 2.),
-    },
-    Aabb2d {
-        min: Vec2::new(POSITION_FOUNDATIONS[3].x - CARD_SIZE.x / 2., POSITION_FOUNDATIONS[3].y - CARD_SIZE.y / 2.),
-        max: Vec2::new(POSITION_FOUNDATIONS[3].x + CARD_SIZE.x / 2., POSITION_FOUNDATIONS[3].y + CARD_SIZE.y / 2.),
-    },
-];
+const PILE_DROP_AREA_TOP: f32 = 88.;
+const PILE_DROP_AREA_BOTTOM: f32 = -144.;
 
 const DRAG_CARD_Z: f32 = 100.;
+const AUTO_COMPLETE_INTERVAL: f32 = 0.15;
+const OFFSET_RECYCLE_TEXT: Vec2 = Vec2::new(6., 34.);
+const TABLE_SIZE: Vec2 = Vec2::new(568., 320.);
 const DRAG_DISTANCE_THRESHOLD: f32 = 5.;
+const SELECTED_CARD_COLOR: Color = Color::srgb(1., 1., 0.6);
+const DOUBLE_CLICK_SECONDS: f32 = 0.3;
+
+pub const MAX_RANDOM_SEED: u64 = 1_000_000_000;
+
+const VEGAS_PASSES_TURN_ONE: u32 = 1;
+const VEGAS_PASSES_TURN_THREE: u32 = 3;
 
 const ATLAS_INDEX_FACEDOWN: usize = 52;
 const ATLAS_INDEX_STOCK_BASE: usize = 57;
 const ATLAS_INDEX_FOUNDATION_BASE: [usize; NUM_SUIT] = [55, 56, 54, 53];
+// 2組で遊ぶときの組札の数
+const MAX_FOUNDATIONS: usize = NUM_SUIT * 2;
 
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, States)]
 pub enum GameState {
     #[default]
     Loading,
+    SelectGame,
     SelectDifficulty,
     Prepare,
     Deal,
     Play,
     NewGame,
     GameClear,
+    GameOver,
 }
 
+#[derive(Clone, Copy)]
 pub enum MoveStep {
     StockToWaste,
+    StockToPile,
     WasteToStock,
     WasteToPile,
     WasteToFoundation,
@@ -113,22 +90,36 @@ pub enum MoveStep {
     PileToFoundation,
     FoundationToPile,
     FaceupPile,
+    PileToFreeCell,
+    FreeCellToPile,
+    FreeCellToFoundation,
+    RemoveCards,
+    TableauToWaste,
+    ReserveToPile,
+    ReserveToFoundation,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub enum CardSuit {
-    Heart,
-    Diamond,
-    Club,
-    Spade,
-}
-
-#[derive(Clone, Copy, Eq, PartialEq)]
+#[derive(Clone, Copy, Deserialize, Eq, PartialEq, Serialize)]
 pub enum CardType {
     Stock,
     Waste,
     Pile(u32),
-    Foundation(CardSuit),
+    Foundation(u32),
+    FreeCell(u32),
+    Pyramid(u32),
+    Peak(u32),
+    Reserve,
+}
+
+/// キーボードやゲームパッドでの操作
+#[derive(Clone, Copy)]
+pub enum CursorAction {
+    Move(Vec2),
+    PickOrPlace,
+    Select,
+    Draw,
+    SendToFoundation,
+    Cancel,
 }
 
 #[derive(Clone, Copy, Component)]
@@ -138,6 +129,7 @@ pub struct CardInfo {
     pub entity: Entity,
     pub card_suit: CardSuit,
     pub card_number: u32,
+    pub deck: u32,
     pub card_type: CardType,
     pub order: i32,
     pub facedown: bool,
@@ -153,6 +145,7 @@ impl Default for CardInfo {
             entity: Entity::PLACEHOLDER,
             card_suit: CardSuit::Heart,
             card_number: 1,
+            deck: 0,
             card_type: CardType::Stock,
             order: 0,
             facedown: true,
@@ -164,6 +157,9 @@ impl Default for CardInfo {
     }
 }
 
+#[derive(Resource)]
+struct CardAtlas(Handle<TextureAtlasLayout>);
+
 #[derive(Component)]
 struct StackInfo {
     target: Entity,
@@ -174,11 +170,6 @@ struct StackInfo {
 #[derive(Component)]
 struct FillWaste;
 
-#[derive(Component)]
-struct AdjustPile {
-    index: u32,
-}
-
 #[derive(Component)]
 struct UpdateZ {
     target: Entity,
@@ -193,12 +184,74 @@ struct UpdateSprite {
 #[derive(Component)]
 struct GameClear;
 
+#[derive(Component)]
+struct AutoComplete {
+    num_recycle: u32,
+}
+
+#[derive(Component)]
+struct AutoCompleteButton;
+
+#[derive(Component)]
+struct PlaySafe;
+
+#[derive(Component)]
+struct SeedSearch(Task<Option<u64>>);
+
+#[derive(Component)]
+struct StockBase;
+
+#[derive(Component)]
+struct RecycleText;
+
+/// ゲームによって数と表示位置が変わる置き場ベース
+#[derive(Component)]
+enum SlotBase {
+    Stock,
+    Foundation(u32),
+    FreeCell(u32),
+}
+
+#[derive(Component)]
+struct Background;
+
+#[derive(Default, Resource)]
+struct CardSelection(Option<Entity>);
+
+#[derive(Default, Resource)]
+struct PeakRun(u32);
+
+/// 遊んでいるゲームの盤面（盤面を使うゲームは移動を盤面に適用し、カードの置き場を盤面に合わせる）
+#[derive(Default, Resource)]
+struct PlayBoard(Option<Board>);
+
+/// クリックで動かしたカードと移動先の候補（同じカードを続けてクリックすると次の候補に移す）
+#[derive(Default, Resource)]
+struct AutoMoveCycle {
+    target: Option<Entity>,
+    dsts: Vec<CardType>,
+    index: usize,
+}
+
+/// ダブルクリックか確かめている組札のクリック（1回目のクリックは少し待ってから処理する）
+#[derive(Default, Resource)]
+struct FoundationClick(Option<(Entity, Timer)>);
+
 #[derive(Event)]
 pub struct LoadEvent;
 
+#[derive(Event)]
+pub struct GameSelectEvent;
+
+#[derive(Event)]
+pub struct ChangeGameEvent;
+
 #[derive(Event)]
 pub struct DifficultySelectEvent;
 
+#[derive(Event)]
+pub struct ContinueEvent;
+
 #[derive(Event)]
 pub struct PrepareEvent;
 
@@ -214,12 +267,58 @@ pub struct CancelNewGameEvent;
 #[derive(Event)]
 pub struct GameClearEvent;
 
+#[derive(Event)]
+pub struct GameOverEvent;
+
 #[derive(Event)]
 pub struct MoveOneStepEvent(pub MoveStep);
 
 #[derive(Event)]
 pub struct AddScoreEvent(pub i32);
 
+#[derive(Event)]
+pub struct HintEvent;
+
+#[derive(Event)]
+pub struct ShowStatsEvent;
+
+/// 画面のボタンを押したとき（クリック、キーボード、ゲームパッド）
+#[derive(Event)]
+pub struct PressButtonEvent;
+
+#[derive(Event)]
+pub struct UndoEvent;
+
+#[derive(Event)]
+pub struct RedoEvent;
+
+#[derive(Event)]
+pub struct RestoreStepEvent(pub MoveStep);
+
+#[derive(Event)]
+pub struct SelectCardEvent;
+
+#[derive(Event)]
+pub struct StackCardEvent(pub Vec<CardType>);
+
+#[derive(Event)]
+pub struct RecycleStockEvent;
+
+#[derive(Event)]
+pub struct TurnStockEvent;
+
+#[derive(Event)]
+pub struct CursorEvent(pub CursorAction);
+
+#[derive(Event)]
+pub struct PlaySafeEvent;
+
+#[derive(Event)]
+pub struct RestoreInformationEvent {
+    pub score: i32,
+    pub movement: u32,
+}
+
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
@@ -227,6 +326,11 @@ impl Plugin for GamePlugin {
         app
             .add_systems(OnExit(GameState::Loading), init_system)
             .add_systems(OnEnter(GameState::Prepare), prepare_system)
+            .add_systems(Update, seed_search_system.run_if(in_state(GameState::Prepare)))
+            .add_systems(Update, update_stock_base_system.run_if(resource_exists_and_changed::<StockRecycle>))
+            .add_systems(Update, update_slot_base_system.run_if(resource_exists_and_changed::<GameKind>))
+            .add_systems(Update, update_table_scale_system.run_if(resource_exists_and_changed::<GameKind>))
+            .add_systems(Update, update_selection_system.run_if(resource_exists_and_changed::<CardSelection>))
             .add_systems(OnEnter(GameState::Deal), deal_system)
             .add_systems(
                 Update,
@@ -234,6 +338,7 @@ impl Plugin for GamePlugin {
                     in_state(GameState::Deal)
                     .or(in_state(GameState::Play))
                     .or(in_state(GameState::GameClear))
+                    .or(in_state(GameState::GameOver))
                     .and(on_timer(Duration::from_secs_f32(0.03)))
                 )
             )
@@ -245,10 +350,6 @@ impl Plugin for GamePlugin {
                 Update,
                 fill_waste_system.run_if(in_state(GameState::Play))
             )
-            .add_systems(
-                Update,
-                adjust_pile_system.run_if(in_state(GameState::Play))
-            )
             .add_systems(
                 Update,
                 update_z_system.run_if(
@@ -267,7 +368,19 @@ impl Plugin for GamePlugin {
             )
             .add_systems(
                 Update,
-                game_clear_system.run_if(in_state(GameState::Play))
+                (
+                    auto_complete_system.run_if(
+                        on_timer(Duration::from_secs_f32(AUTO_COMPLETE_INTERVAL))
+                    ),
+                    auto_complete_button_system,
+                    play_safe_system.run_if(
+                        on_timer(Duration::from_secs_f32(AUTO_COMPLETE_INTERVAL))
+                    ),
+                    foundation_click_system,
+                    selection_system.run_if(resource_changed::<CardList>),
+                    game_clear_system,
+                    game_over_system.run_if(resource_changed::<CardList>),
+                ).run_if(in_state(GameState::Play))
             );
     }
 }
@@ -289,7 +402,9 @@ fn init_system(
     commands.spawn((
         Sprite::from_image(game_textures.background.clone()),
         Transform::from_translation(Vec3::new(0., 0., -2.)),
-    ));
+        Background,
+    ))
+    .observe(on_click_table);
 
     // 山札ベース
     commands.spawn((
@@ -302,26 +417,59 @@ fn init_system(
             ..Default::default()
         },
         Transform::from_translation(
-            Vec3::new(POSITION_STOCK.x, POSITION_STOCK.y, -1.)
+            POSITION_STOCK.extend(-1.)
         ),
+        StockBase,
+        SlotBase::Stock,
     ))
-    .observe(on_click_stock_base);
+    .observe(on_click_stock_base)
+    // 山札を戻せる残り回数（山札ベースと一緒に動かす）
+    .with_child((
+        Text2d::new(""),
+        TextFont {
+            font: game_fonts.dot_gothic.clone(),
+            font_size: 12.,
+            ..Default::default()
+        },
+        TextColor(Color::WHITE),
+        Transform::from_translation(OFFSET_RECYCLE_TEXT.extend(0.)),
+        RecycleText,
+    ));
 
-    // 組札ベース
-    for index in 0..NUM_SUIT {
+    // 組札ベース（表示位置と数はゲームに合わせて決める）
+    for index in 0..MAX_FOUNDATIONS {
         commands.spawn((
             Sprite {
                 image: game_textures.card.clone(),
                 texture_atlas: Some(TextureAtlas {
                     layout: layout.clone(),
-                    index: ATLAS_INDEX_FOUNDATION_BASE[index],
+                    index: ATLAS_INDEX_FOUNDATION_BASE[foundation_suit(index) as usize],
                 }),
                 ..Default::default()
             },
-            Transform::from_translation(
-                Vec3::new(POSITION_FOUNDATIONS[index].x, POSITION_FOUNDATIONS[index].y, -1.)
-            ),
-        ));
+            Transform::from_translation(Vec3::new(0., 0., -1.)),
+            Visibility::Hidden,
+            SlotBase::Foundation(index as u32),
+        ))
+        .observe(on_click_table);
+    }
+
+    // フリーセルベース（表示位置はゲームに合わせて決める）
+    for index in 0..NUM_FREE_CELLS {
+        commands.spawn((
+            Sprite {
+                image: game_textures.card.clone(),
+                texture_atlas: Some(TextureAtlas {
+                    layout: layout.clone(),
+                    index: ATLAS_INDEX_STOCK_BASE,
+                }),
+                ..Default::default()
+            },
+            Transform::from_translation(Vec3::new(0., 0., -1.)),
+            Visibility::Hidden,
+            SlotBase::FreeCell(index as u32),
+        ))
+        .observe(on_click_table);
     }
 
     // 新しいゲーム
@@ -343,56 +491,345 @@ fn init_system(
     ))
     .observe(on_click_new_game);
 
+    // 元に戻す
+    commands.spawn((
+        Sprite {
+            custom_size: Some(Vec2::new(64., 32.)),
+            color: Color::Srgba(Srgba::NONE),
+            ..Default::default()
+        },
+        Text2d::new("元に戻す"),
+        TextFont {
+            font: game_fonts.dot_gothic.clone(),
+            font_size: 16.,
+            ..Default::default()
+        },
+        TextColor(Color::WHITE),
+        TextBounds::from(Vec2::new(64., 32.)),
+        Transform::from_translation(Vec3::new(-25., -130., -1.)),
+    ))
+    .observe(on_click_undo);
+
+    // やり直す
+    commands.spawn((
+        Sprite {
+            custom_size: Some(Vec2::new(64., 32.)),
+            color: Color::Srgba(Srgba::NONE),
+            ..Default::default()
+        },
+        Text2d::new("やり直す"),
+        TextFont {
+            font: game_fonts.dot_gothic.clone(),
+            font_size: 16.,
+            ..Default::default()
+        },
+        TextColor(Color::WHITE),
+        TextBounds::from(Vec2::new(64., 32.)),
+        Transform::from_translation(Vec3::new(45., -130., -1.)),
+    ))
+    .observe(on_click_redo);
+
+    // ヒント
+    commands.spawn((
+        Sprite {
+            custom_size: Some(Vec2::new(48., 32.)),
+            color: Color::Srgba(Srgba::NONE),
+            ..Default::default()
+        },
+        Text2d::new("ヒント"),
+        TextFont {
+            font: game_fonts.dot_gothic.clone(),
+            font_size: 16.,
+            ..Default::default()
+        },
+        TextColor(Color::WHITE),
+        TextBounds::from(Vec2::new(48., 32.)),
+        Transform::from_translation(Vec3::new(-87., -130., -1.)),
+    ))
+    .observe(on_click_hint);
+
+    // 自動完了
+    commands.spawn((
+        Sprite {
+            custom_size: Some(Vec2::new(64., 32.)),
+            color: Color::Srgba(Srgba::NONE),
+            ..Default::default()
+        },
+        Text2d::new("自動完了"),
+        TextFont {
+            font: game_fonts.dot_gothic.clone(),
+            font_size: 16.,
+            ..Default::default()
+        },
+        TextColor(Color::WHITE),
+        TextBounds::from(Vec2::new(64., 32.)),
+        Transform::from_translation(Vec3::new(-163., -130., -1.)),
+        Visibility::Hidden,
+        AutoCompleteButton,
+    ))
+    .observe(on_click_auto_complete);
+
+    commands.add_observer(on_undo);
+    commands.add_observer(on_redo);
+    commands.add_observer(on_continue);
+    commands.add_observer(on_recycle_stock);
+    commands.add_observer(on_turn_stock);
+    commands.add_observer(on_play_safe);
+
     // カード
-    let mut card_list = Vec::new();
-    for suit in [CardSuit::Heart, CardSuit::Diamond, CardSuit::Club, CardSuit::Spade] {
-        for number in 1..=13 {
-            let entity = commands.spawn((
-                Card,
-                Sprite {
-                    image: game_textures.card.clone(),
-                    texture_atlas: Some(TextureAtlas {
-                        layout: layout.clone(),
-                        index: ATLAS_INDEX_FACEDOWN,
-                    }),
-                    ..Default::default()
-                },
-                Visibility::Hidden
-            ))
-            .observe(on_click_card)
-            .observe(on_drag_start)
-            .observe(on_drag)
-            .observe(on_drag_end)
-            .id();
-
-            let card = CardInfo {
-                entity: entity,
-                card_suit: suit,
-                card_number: number,
-                ..Default::default()
-            };
-            card_list.push(card);
+    let card_atlas = CardAtlas(layout);
+    let mut card_list = CardList(Vec::new());
+    resize_card_list(&mut commands, &mut card_list, &game_textures, &card_atlas, GameKind::Klondike);
+
+    commands.insert_resource(card_list);
+    commands.insert_resource(card_atlas);
+    commands.insert_resource(GameKind::default());
+    commands.insert_resource(CurrentOptions::default());
+    commands.insert_resource(CardSelection::default());
+    commands.insert_resource(PeakRun::default());
+    commands.insert_resource(PlayBoard::default());
+    commands.insert_resource(AutoMoveCycle::default());
+    commands.insert_resource(FoundationClick::default());
+    commands.insert_resource(DealSeed::default());
+    commands.insert_resource(ScoringMode::default());
+    commands.insert_resource(DrawRule::default());
+    commands.insert_resource(StockRecycle::default());
+}
+
+/// ゲームに合わせてカードを追加・削除
+fn resize_card_list(
+    commands: &mut Commands,
+    card_list: &mut CardList,
+    game_textures: &GameTextures,
+    card_atlas: &CardAtlas,
+    game: GameKind,
+) {
+    while card_list.0.len() > game.ruleset().num_cards() {
+        if let Some(card) = card_list.0.pop() {
+            commands.entity(card.entity).despawn_recursive();
         }
     }
 
-    commands.insert_resource(CardList(card_list));
+    while card_list.0.len() < game.ruleset().num_cards() {
+        let entity = commands.spawn((
+            Card,
+            Sprite {
+                image: game_textures.card.clone(),
+                texture_atlas: Some(TextureAtlas {
+                    layout: card_atlas.0.clone(),
+                    index: ATLAS_INDEX_FACEDOWN,
+                }),
+                ..Default::default()
+            },
+            Visibility::Hidden
+        ))
+        .observe(on_click_card)
+        .observe(on_select_card)
+        .observe(on_stack_card)
+        .observe(on_drag_start)
+        .observe(on_drag)
+        .observe(on_drag_end)
+        .id();
+
+        card_list.0.push(CardInfo {
+            entity,
+            ..Default::default()
+        });
+    }
 }
 
 /// シャッフルして山札に配置
 fn prepare_system(
     mut commands: Commands,
     mut card_list: ResMut<CardList>,
-    mut query: Query<(&mut Transform, &mut Visibility), With<Card>>,
+    mut deal_seed: ResMut<DealSeed>,
+    mut analysis: ResMut<DealAnalysis>,
+    mut stock_recycle: ResMut<StockRecycle>,
+    draw: Res<DrawRule>,
+    options: Res<GameOptions>,
+    game_textures: Res<GameTextures>,
+    card_atlas: Res<CardAtlas>,
+    query_auto_complete: Query<Entity, With<AutoComplete>>,
+    query_play_safe: Query<Entity, With<PlaySafe>>,
+) {
+    reset_play_state(&mut commands, &query_auto_complete, &query_play_safe);
+
+    // 遊ぶゲームに合わせてカードを用意する
+    commands.insert_resource(options.game);
+    commands.insert_resource(CurrentOptions(*options));
+    resize_card_list(&mut commands, &mut card_list, &game_textures, &card_atlas, options.game);
+
+    // 得点方式と山札を戻せる回数を決める
+    let ruleset = options.game.ruleset();
+    commands.insert_resource(ruleset.scoring(&options));
+    *stock_recycle = StockRecycle {
+        count: 0,
+        limit: ruleset.max_recycle(&draw, &options),
+    };
+
+    if deal_seed.fixed {
+        deal_seed.fixed = false;
+        let deck = ruleset.deck(deal_seed.seed, &options);
+        shuffle_cards(&mut commands, &mut card_list, options.game, deck);
+        return;
+    }
+
+    // 勝てる配布のみの場合はバックグラウンドで配布番号を探す
+    if options.winnable_only && ruleset.has_option(GameOption::WinnableOnly) {
+        let num_turn = draw.num_turn;
+        let max_recycle = stock_recycle.limit;
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            solver::find_winnable_seed(num_turn, max_recycle)
+        });
+        commands.spawn(SeedSearch(task));
+        analysis.set_searching();
+        return;
+    }
+
+    // 配布番号の指定がなければランダムに決める
+    deal_seed.seed = rand::thread_rng().gen_range(0..MAX_RANDOM_SEED);
+    let deck = ruleset.deck(deal_seed.seed, &options);
+    shuffle_cards(&mut commands, &mut card_list, options.game, deck);
+}
+
+/// 前のゲームの選択中のカードや自動で動かしている途中の処理を取り消す
+fn reset_play_state(
+    commands: &mut Commands,
+    query_auto_complete: &Query<Entity, With<AutoComplete>>,
+    query_play_safe: &Query<Entity, With<PlaySafe>>,
+) {
+    for entity in query_auto_complete.iter().chain(query_play_safe.iter()) {
+        commands.entity(entity).despawn();
+    }
+    commands.insert_resource(CardSelection::default());
+    commands.insert_resource(FoundationClick::default());
+    commands.insert_resource(PeakRun::default());
+    commands.insert_resource(AutoMoveCycle::default());
+}
+
+/// 山札ベースと残り回数の表示更新
+fn update_stock_base_system(
+    stock_recycle: Res<StockRecycle>,
+    mut query_base: Query<&mut Sprite, With<StockBase>>,
+    mut query_text: Query<&mut Text2d, With<RecycleText>>,
+) {
+    let remaining = stock_recycle.limit.map(|limit| limit.saturating_sub(stock_recycle.count));
+
+    // 戻せなくなったら山札ベースを暗くする
+    if let Ok(mut sprite) = query_base.get_single_mut() {
+        sprite.color = if remaining == Some(0) {
+            Color::srgba(1., 1., 1., 0.3)
+        } else {
+            Color::WHITE
+        };
+    }
+    if let Ok(mut text) = query_text.get_single_mut() {
+        text.0 = remaining.map_or_else(String::new, |remaining| format!("残り{}回", remaining));
+    }
+}
+
+/// ゲームに合わせた置き場ベースの表示切り替え
+fn update_slot_base_system(
+    game: Res<GameKind>,
+    mut query: Query<(&SlotBase, &mut Transform, &mut Visibility)>,
 ) {
-    let mut suit_and_num = Vec::new();
-    for suit in [CardSuit::Heart, CardSuit::Diamond, CardSuit::Club, CardSuit::Spade] {
-        for number in 1..=13 {
-            suit_and_num.push((suit, number));
+    // ゲームで使う数だけ山札ベース、組札ベース、フリーセルベースを並べる
+    let ruleset = game.ruleset();
+    for (base, mut transform, mut visibility) in query.iter_mut() {
+        let (card_type, index, num) = match *base {
+            SlotBase::Stock => (CardType::Stock, 0, ruleset.has_stock() as usize),
+            SlotBase::Foundation(index) => (CardType::Foundation(index), index, ruleset.num_foundations()),
+            SlotBase::FreeCell(index) => (CardType::FreeCell(index), index, ruleset.num_free_cells()),
+        };
+        if (index as usize) < num {
+            transform.translation = get_slot_position(*game, card_type).extend(-1.);
+            visibility.set_if_neq(Visibility::Inherited);
+        } else {
+            visibility.set_if_neq(Visibility::Hidden);
         }
     }
-    let mut rng = rand::thread_rng();
-    suit_and_num.shuffle(&mut rng);
+}
+
+/// ゲームに合わせた盤面の表示倍率の切り替え
+fn update_table_scale_system(
+    game: Res<GameKind>,
+    mut query_camera: Query<&mut OrthographicProjection, With<Camera2d>>,
+    mut query_background: Query<&mut Sprite, With<Background>>,
+) {
+    let scale = game.ruleset().table_scale();
+    for mut projection in query_camera.iter_mut() {
+        projection.scale = scale;
+    }
+
+    // 縮小した分だけ背景を広げて画面を覆う
+    for mut sprite in query_background.iter_mut() {
+        sprite.custom_size = Some(TABLE_SIZE * scale);
+    }
+}
+
+/// 選んだカードの強調表示
+fn update_selection_system(
+    selection: Res<CardSelection>,
+    card_list: Res<CardList>,
+    mut query: Query<(Entity, &mut Sprite), With<Card>>,
+) {
+    // 選んだカードにつながったカードも一緒に目立たせる
+    let mut selected: Vec<Entity> = selection.0.into_iter().collect();
+    if let Some(cards) = selection.0.and_then(|entity| card_list.get_connected_cards(entity)) {
+        selected.extend(cards.into_iter().map(|card| card.entity));
+    }
+
+    for (entity, mut sprite) in query.iter_mut() {
+        let color = if selected.contains(&entity) {
+            SELECTED_CARD_COLOR
+        } else {
+            Color::WHITE
+        };
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
+
+/// 選んだカードが選べなくなったら選択を解除
+fn selection_system(
+    card_list: Res<CardList>,
+    mut selection: ResMut<CardSelection>,
+) {
+    if selection.0.is_some_and(|entity| !card_list.get(entity).is_some_and(|card| card.clickable)) {
+        selection.0 = None;
+    }
+}
+
+/// 勝てる配布の探索結果の受け取り
+fn seed_search_system(
+    mut commands: Commands,
+    mut card_list: ResMut<CardList>,
+    mut deal_seed: ResMut<DealSeed>,
+    game: Res<GameKind>,
+    options: Res<CurrentOptions>,
+    mut query_search: Query<(Entity, &mut SeedSearch)>,
+) {
+    for (entity, mut search) in query_search.iter_mut() {
+        let Some(seed) = block_on(poll_once(&mut search.0)) else {
+            continue;
+        };
+        commands.entity(entity).despawn();
+
+        // 見つからなければランダムな配布にする
+        deal_seed.seed = seed.unwrap_or_else(|| rand::thread_rng().gen_range(0..MAX_RANDOM_SEED));
+        let deck = game.ruleset().deck(deal_seed.seed, &options);
+        shuffle_cards(&mut commands, &mut card_list, *game, deck);
+    }
+}
 
+/// シャッフルしたカードを山札に並べる
+fn shuffle_cards(
+    commands: &mut Commands,
+    card_list: &mut CardList,
+    game: GameKind,
+    mut suit_and_num: Vec<(CardSuit, u32)>,
+) {
     let mut order = 0;
     for card in card_list.0.iter_mut() {
         if let Some((suit, number)) = suit_and_num.pop() {
@@ -407,13 +844,14 @@ fn prepare_system(
                 target: card.entity,
             });
 
-            let position = Vec3::new(POSITION_STOCK.x, POSITION_STOCK.y, order as f32);
+            // 追加したばかりのカードもあるため、コマンドで配置する
+            let position = get_slot_position(game, CardType::Stock).extend(order as f32);
             card.dst_position = position;
-            if let Ok((mut transform, mut visibility)) = query.get_mut(card.entity) {
-                transform.translation = position;
-                *visibility = Visibility::Visible;
-            }
-            
+            commands.entity(card.entity).insert((
+                Transform::from_translation(position),
+                Visibility::Visible,
+            ));
+
             commands.spawn(UpdateZ {
                 target: card.entity,
                 value: order as f32,
@@ -423,6 +861,7 @@ fn prepare_system(
         }
     }
 
+    card_list.assign_decks();
     commands.trigger(PrepareEvent);
 }
 
@@ -430,31 +869,40 @@ fn prepare_system(
 fn deal_system(
     mut commands: Commands,
     mut card_list: ResMut<CardList>,
+    mut play_board: ResMut<PlayBoard>,
+    draw: Res<DrawRule>,
+    scoring: Res<ScoringMode>,
+    game: Res<GameKind>,
+    stock_recycle: Res<StockRecycle>,
 ) {
-    let mut pile_index = 0;
-    let mut order = 0;
-    for card in card_list.0.iter_mut() {
-        card.card_type = CardType::Pile(pile_index);
-        card.order = order;
+    let ruleset = game.ruleset();
+    for (card, slot) in card_list.0.iter_mut().zip(ruleset.deal()) {
+        // 組札に配るカードはスートごとの置き場に置く（キャンフィールド）
+        card.card_type = match slot.card_type {
+            CardType::Foundation(_) => CardType::Foundation(card.card_suit as u32),
+            card_type => card_type,
+        };
+        card.order = slot.order;
+        card.clickable = slot.clickable;
 
-        let facedown = order != pile_index as i32;
-        card.facedown = facedown;
+        card.facedown = slot.facedown;
         commands.spawn(UpdateSprite {
             target: card.entity,
         });
+    }
 
-        let position = calc_pile_position(pile_index, order, pile_index, 1);
+    // 配り終えた枚数に合わせて表示位置を決める
+    let positions: Vec<Vec3> = card_list.0.iter().map(|card| get_card_position(&card_list, *game, card)).collect();
+    for (card, position) in card_list.0.iter_mut().zip(positions) {
         card.dst_position = position;
-        card.clickable = !facedown;
+    }
 
-        order += 1;
-        if order > pile_index as i32 {
-            pile_index += 1;
-            order = 0;
-        }
-        if pile_index >= NUM_PILES as u32 {
-            break;
-        }
+    // 盤面を使うゲームは配った状態から盤面を作る
+    play_board.0 = ruleset.board(&card_list, &draw, &stock_recycle);
+
+    let score = ruleset.deal_score(*scoring);
+    if score != 0 {
+        commands.trigger(AddScoreEvent(score));
     }
 
     commands.trigger(DealEvent);
@@ -489,317 +937,601 @@ fn movement_system(
 fn stack_system(
     mut commands: Commands,
     mut card_list: ResMut<CardList>,
-    difficulty: Res<GameDifficulty>,
+    mut history: ResMut<History>,
+    mut play_board: ResMut<PlayBoard>,
+    scoring: Res<ScoringMode>,
+    game: Res<GameKind>,
+    stock_recycle: Res<StockRecycle>,
+    score: Res<Score>,
+    movement: Res<Movement>,
     query: Query<(Entity, &StackInfo)>,
+    query_auto_complete: Query<(), With<AutoComplete>>,
 ) {
+    let ruleset = game.ruleset();
     for (entity, stackinfo) in query.iter() {
-        let mut dst_order = 0;
-        let mut dst_card_type = CardType::Stock;
-        let mut dst_position = Vec3::default();
-        let mut src_card_type = CardType::Stock;
-        let mut src_order = 0;
-        let mut move_step = MoveStep::StockToWaste;
-        let mut should_move = false;
-
-        if let Some(card) = card_list.get(stackinfo.target) {
-            src_card_type = card.card_type;
-            src_order = card.order;
-
-            match (&card.card_type, &stackinfo.dst) {
-                // 山札からめくったカード -> 場札の移動判定
-                (CardType::Waste, CardType::Pile(index)) => {
-                    dst_order = stackinfo.order;
-                    dst_card_type = CardType::Pile(*index);
-                    let pos = calc_pile_position(
-                        *index,
-                        stackinfo.order,
-                        card_list.num_facedown(*index),
-                        card_list.num_faceup(*index)
-                    );
-                    dst_position = Vec3::new(pos.x, pos.y, stackinfo.order as f32);
-
-                    move_step = MoveStep::WasteToPile;
-                    should_move = true;
-
-                    commands.spawn(FillWaste{});
-                    commands.trigger(AddScoreEvent(get_score(MoveStep::WasteToPile)));
-                }
+        commands.entity(entity).despawn();
 
-                // 山札からめくったカード -> 組札の移動判定
-                (CardType::Waste, CardType::Foundation(suit)) => {
-                    dst_order = stackinfo.order;
-                    dst_card_type = CardType::Foundation(*suit);
-                    let pos = POSITION_FOUNDATIONS[*suit as usize];
-                    dst_position = Vec3::new(pos.x, pos.y, stackinfo.order as f32);
-
-                    move_step = MoveStep::WasteToFoundation;
-                    should_move = true;
-
-                    if is_game_clear(&card_list.0, num_turn_to_waste(*difficulty)) {
-                        commands.spawn(GameClear{});
-                    } else {
-                        commands.spawn(FillWaste{});
-                    }
-                    commands.trigger(AddScoreEvent(get_score(MoveStep::WasteToFoundation)));
+        // ルール上で移動できれば、つながったカードと一緒に移動先に移す
+        let can_move = card_list.get(stackinfo.target).is_some_and(|card| {
+            ruleset.stack_order(&card_list, card, stackinfo.dst).is_some()
+        });
+        let before = Snapshot::new(&card_list, score.0, movement.0, &stock_recycle);
+        let moved = can_move && match play_board.0.as_mut() {
+            Some(board) => match card_list.get_board_move(stackinfo.target, stackinfo.dst).and_then(|mv| board.apply(mv)) {
+                Some(applied) => {
+                    card_list.sync_board(board);
+                    commit_board_move(&mut commands, &mut card_list, &mut history, *game, *scoring, before, applied)
+                }
+                None => false,
+            },
+            None => {
+                // ゲームごとの移動後の処理も同じ手として記録する
+                let mut steps = card_list.move_cards(stackinfo.target, stackinfo.dst, stackinfo.order);
+                if !steps.is_empty() {
+                    steps.extend(ruleset.after_move(&mut card_list, stackinfo.dst));
                 }
+                commit_move(&mut commands, &mut card_list, &mut history, *game, *scoring, before, &steps)
+            }
+        };
 
-                // 場札 -> 場札の移動判定
-                (CardType::Pile(_), CardType::Pile(index)) => {
-                    dst_order = stackinfo.order;
-                    dst_card_type = CardType::Pile(*index);
-                    let pos = calc_pile_position(
-                        *index,
-                        stackinfo.order,
-                        card_list.num_facedown(*index),
-                        card_list.num_faceup(*index)
-                    );
-                    dst_position = Vec3::new(pos.x, pos.y, stackinfo.order as f32);
-
-                    move_step = MoveStep::PileToPile;
-                    should_move = true;
-
-                    commands.trigger(AddScoreEvent(get_score(MoveStep::PileToPile)));
+        // 移動できなければ、カードとつながったカードをもどす
+        if !moved {
+            if let Some(card) = card_list.get_mut(stackinfo.target) {
+                card.dst_position = card.prev_position;
+            }
+            if let Some(cards) = card_list.get_connected_cards_mut(stackinfo.target) {
+                for card in cards {
+                    card.dst_position = card.prev_position;
                 }
+            }
+            continue;
+        }
 
-                // 場札 -> 組札の移動判定
-                (CardType::Pile(_), CardType::Foundation(suit)) => {
-                    dst_order = stackinfo.order;
-                    dst_card_type = CardType::Foundation(*suit);
-                    let pos = POSITION_FOUNDATIONS[*suit as usize];
-                    dst_position = Vec3::new(pos.x, pos.y, stackinfo.order as f32);
+        // 組札に移したときは残りを自動で完了できるかを確認
+        if matches!(stackinfo.dst, CardType::Foundation(_))
+            && ruleset.can_auto_complete()
+            && !ruleset.is_won(&card_list)
+            && query_auto_complete.is_empty()
+            && play_board.0.as_ref().is_some_and(|board| board.is_game_clear()) {
+            commands.spawn(AutoComplete { num_recycle: 0 });
+        }
+    }
+}
 
-                    move_step = MoveStep::PileToFoundation;
-                    should_move = true;
+/// 盤面に適用した移動を記録して、カードの表示を更新する（カードの置き場は盤面に合わせておく）
+fn commit_board_move(
+    commands: &mut Commands,
+    card_list: &mut CardList,
+    history: &mut History,
+    game: GameKind,
+    scoring: ScoringMode,
+    before: Snapshot,
+    applied: AppliedMove,
+) -> bool {
+    commit_move(commands, card_list, history, game, scoring, before, &get_board_move_steps(&applied));
+    history.push_applied(applied);
+    true
+}
 
-                    if is_game_clear(&card_list.0, num_turn_to_waste(*difficulty)) {
-                        commands.spawn(GameClear{});
-                    }
-                    commands.trigger(AddScoreEvent(get_score(MoveStep::PileToFoundation)));
-                }
+/// 動かした手を記録して、カードの表示を更新する（動かしたかどうかを返す）
+fn commit_move(
+    commands: &mut Commands,
+    card_list: &mut CardList,
+    history: &mut History,
+    game: GameKind,
+    scoring: ScoringMode,
+    before: Snapshot,
+    steps: &[MoveStep],
+) -> bool {
+    let Some((first, rest)) = steps.split_first() else {
+        return false;
+    };
 
-                // 組札 -> 場札の移動判定
-                (CardType::Foundation(_), CardType::Pile(index)) => {
-                    dst_order = stackinfo.order;
-                    dst_card_type = CardType::Pile(*index);
-                    let pos = calc_pile_position(
-                        *index,
-                        stackinfo.order,
-                        card_list.num_facedown(*index),
-                        card_list.num_faceup(*index)
-                    );
-                    dst_position = Vec3::new(pos.x, pos.y, stackinfo.order as f32);
-
-                    move_step = MoveStep::FoundationToPile;
-                    should_move = true;
-
-                    commands.trigger(AddScoreEvent(get_score(MoveStep::FoundationToPile)));
-                }
+    update_cards(commands, card_list, game, &before.cards);
+    history.push(*first, before);
+    for step in rest {
+        history.push_step(*step);
+    }
 
-                (_, _) => {}
-            }
+    let ruleset = game.ruleset();
+    commands.trigger(MoveOneStepEvent(*first));
+    for step in steps {
+        commands.trigger(AddScoreEvent(ruleset.score(*step, scoring)));
+    }
+
+    if ruleset.is_won(card_list) {
+        commands.spawn(GameClear{});
+    }
+    true
+}
+
+/// カードの表示を置き場に合わせて更新（置き場が変わったカードは手前に表示し、裏表が変わったカードは画像を変える）
+fn update_cards(
+    commands: &mut Commands,
+    card_list: &mut CardList,
+    game: GameKind,
+    before: &[CardLocation],
+) {
+    for (card, location) in card_list.0.iter().zip(before) {
+        if card.card_type != location.card_type {
+            commands.spawn(UpdateZ {
+                target: card.entity,
+                value: DRAG_CARD_Z + card.order as f32,
+            });
+        }
+        if card.facedown != location.facedown {
+            commands.spawn(UpdateSprite {
+                target: card.entity,
+            });
         }
+    }
 
-        // カードを移動
-        if let Some(card) = card_list.get_mut(stackinfo.target) {
-            if should_move {
-                card.order = dst_order;
-                card.card_type = dst_card_type;
-                card.dst_position = dst_position;
-                commands.trigger(MoveOneStepEvent(move_step));
-            } else {
-                card.dst_position = card.prev_position;
+    // 山札から引いたカードはあとでずらして並べる
+    let positions: Vec<Vec3> = card_list.0.iter().map(|card| get_card_position(card_list, game, card)).collect();
+    for (card, position) in card_list.0.iter_mut().zip(positions) {
+        card.dst_position = position;
+    }
+    commands.spawn(FillWaste{});
+}
+
+/// 山札から引いたカードの補充
+fn fill_waste_system(
+    mut commands: Commands,
+    mut card_list: ResMut<CardList>,
+    draw: Res<DrawRule>,
+    game: Res<GameKind>,
+    query_fill_waste: Query<Entity, With<FillWaste>>,
+) {
+    for entity in query_fill_waste.iter() {
+        if let Some(mut waste_cards) = card_list.get_waste_cards_mut() {
+            // 上から決められた枚数だけずらして並べ、それより下のカードは一番奥に重ねる
+            let num_hidden = waste_cards.len().saturating_sub(draw.num_fan as usize);
+            for (index, card) in waste_cards.iter_mut().enumerate() {
+                card.order = index as i32;
+                card.clickable = false;
+
+                let mut position = get_slot_position(*game, CardType::Waste);
+                position.y -= index.saturating_sub(num_hidden) as f32 * OFFSET_WASTE_Y;
+                (card.dst_position.x, card.dst_position.y) = (position.x, position.y);
+            }
+
+            if let Some(card) = waste_cards.last_mut() {
+                card.clickable = true;
             }
         }
+        commands.entity(entity).despawn();
+    }
+}
 
-        if let CardType::Pile(index) = src_card_type {
-            if let Some(mut cards) = card_list.get_pile_cards_mut(index) {
-                if should_move {
-                    // つながっていたカードを移動
-                    let mut offset = 1;
-                    for card in cards.iter_mut() {
-                        if card.order > src_order {
-                            card.order = dst_order + offset;
-                            card.card_type = dst_card_type;
-                            offset += 1;
-                        }
-                    }
-                } else {
-                    // つながっていたカードをもどす
-                    for card in cards {
-                        if card.order > src_order {
-                            card.dst_position = card.prev_position;
-                        }
-                    }
-                }
+/// カード表示順の更新
+fn update_z_system(
+    mut commands: Commands,
+    card_list: ResMut<CardList>,
+    mut query_transform: Query<&mut Transform, With<Card>>,
+    query_update_z: Query<(Entity, &UpdateZ)>,
+) {
+    for (entity, update_z) in query_update_z.iter() {
+        if let Some(card) = card_list.get(update_z.target) {
+            if let Ok(mut transform) = query_transform.get_mut(card.entity) {
+                transform.translation.z = update_z.value;
             }
+        }
+        commands.entity(entity).despawn();
+    }
+}
 
-            // 移動元の場札列の手前のカードをめくる
-            if should_move {
-                if let Some(mut cards) = card_list.get_pile_cards_mut(index) {
-                    if let Some(last) = cards.last_mut() {
-                        if last.facedown {
-                            last.clickable = true;
-                            last.facedown = false;
-                            commands.spawn(UpdateSprite {
-                                target: last.entity,
-                            });
-                            commands.trigger(AddScoreEvent(get_score(MoveStep::FaceupPile)));
-                        }
-                    }
+/// カード画像の更新
+fn update_sprite_system(
+    mut commands: Commands,
+    card_list: ResMut<CardList>,
+    mut query_sprite: Query<&mut Sprite, With<Card>>,
+    query_update_sprite: Query<(Entity, &UpdateSprite)>,
+) {
+    for (entity, update_sprite) in query_update_sprite.iter() {
+        if let Some(card) = card_list.get(update_sprite.target) {
+            if let Ok(mut sprite) = query_sprite.get_mut(card.entity) {
+                if card.facedown {
+                    facedown_card(sprite.texture_atlas.as_mut());
+                } else {
+                    faceup_card(card.card_suit, card.card_number, sprite.texture_atlas.as_mut());
                 }
             }
         }
+        commands.entity(entity).despawn();
+    }
+}
 
-        // 場札の表示位置を更新
-        if should_move {
-            if let CardType::Pile(index) = src_card_type {
-                commands.spawn(AdjustPile {
-                    index: index,
-                });
-            }
-            if let CardType::Pile(index) = dst_card_type {
-                commands.spawn(AdjustPile {
-                    index: index,
-                });
-            }
+/// 自動完了処理（1回につき1枚ずつ組札に移動）
+fn auto_complete_system(
+    mut commands: Commands,
+    card_list: Res<CardList>,
+    play_board: Res<PlayBoard>,
+    mut query: Query<(Entity, &mut AutoComplete)>,
+    query_stack_info: Query<(), With<StackInfo>>,
+) {
+    let Some((entity, mut auto_complete)) = query.iter_mut().next() else {
+        return;
+    };
+    if !query_stack_info.is_empty() || card_list.0.iter().any(|card| card.dragging) {
+        return;
+    }
+    let Some(board) = play_board.0.as_ref() else {
+        commands.entity(entity).despawn();
+        return;
+    };
+
+    // 数字の小さいカードから順に組札に移動（2組のときは重ねられる最初の置き場）
+    let next = board.waste.last().into_iter()
+        .chain(board.piles.iter().filter_map(|pile| pile.last()))
+        .filter(|card| !card.facedown)
+        .filter_map(|card| board.foundation_slot(card).map(|slot| (card, slot)))
+        .min_by_key(|(card, _)| card.number);
+    if let Some((card, slot)) = next {
+        auto_complete.num_recycle = 0;
+        let card = &card_list.0[card.id];
+        commands.spawn(StackInfo {
+            target: card.entity,
+            dst: CardType::Foundation(slot as u32),
+            order: card.card_number as i32,
+        });
+        commands.spawn(UpdateZ {
+            target: card.entity,
+            value: DRAG_CARD_Z,
+        });
+        return;
+    }
+
+    // 山札を一巡しても動かせるカードがなければ中断
+    if board.stock.is_empty() && auto_complete.num_recycle > 0 {
+        commands.entity(entity).despawn();
+        return;
+    }
+
+    if !board.stock.is_empty() {
+        commands.trigger(TurnStockEvent);
+    } else if board.can_apply(Move::WasteToStock) {
+        auto_complete.num_recycle += 1;
+        commands.trigger(RecycleStockEvent);
+    } else {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// 組札に移しても困らないカードを1枚ずつ組札に移動
+fn play_safe_system(
+    mut commands: Commands,
+    card_list: Res<CardList>,
+    game: Res<GameKind>,
+    query: Query<Entity, With<PlaySafe>>,
+    query_stack_info: Query<(), With<StackInfo>>,
+) {
+    let Some(entity) = query.iter().next() else {
+        return;
+    };
+    if !query_stack_info.is_empty() || card_list.0.iter().any(|card| card.dragging) {
+        return;
+    }
+
+    // 数字の小さいカードから順に移動し、なくなったら終了
+    let ruleset = game.ruleset();
+    let num_foundations = ruleset.num_foundations();
+    let next = card_list.0.iter().filter(|card| {
+        card.clickable && matches!(
+            card.card_type,
+            CardType::Waste | CardType::Pile(_) | CardType::FreeCell(_) | CardType::Reserve
+        )
+    }).filter_map(|card| {
+        (0..num_foundations as u32).find_map(|slot| {
+            ruleset.stack_order(&card_list, card, CardType::Foundation(slot)).map(|order| (card, slot, order))
+        })
+    }).filter(|(card, _, order)| {
+        card_list.is_safe_foundation(card, *order, num_foundations)
+    }).min_by_key(|(_, _, order)| *order);
+
+    let Some((card, slot, order)) = next else {
+        commands.entity(entity).despawn();
+        return;
+    };
+    commands.spawn(StackInfo {
+        target: card.entity,
+        dst: CardType::Foundation(slot),
+        order,
+    });
+    raise_cards(&mut commands, &card_list, card);
+}
+
+/// ダブルクリックでなかった組札のクリックを処理
+fn foundation_click_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut foundation_click: ResMut<FoundationClick>,
+) {
+    let Some((entity, timer)) = foundation_click.0.as_mut() else {
+        return;
+    };
+    if timer.tick(time.delta()).finished() {
+        commands.trigger_targets(SelectCardEvent, *entity);
+        foundation_click.0 = None;
+    }
+}
+
+/// 「自動完了」の表示切り替え
+fn auto_complete_button_system(
+    card_list: Res<CardList>,
+    game: Res<GameKind>,
+    query_auto_complete: Query<(), With<AutoComplete>>,
+    mut query: Query<&mut Visibility, With<AutoCompleteButton>>,
+) {
+    // 残りのカードがすべて表向きになったら表示する
+    let visible = game.ruleset().can_auto_complete()
+        && query_auto_complete.is_empty()
+        && card_list.0.iter().all(|card| !card.facedown)
+        && card_list.0.iter().any(|card| !matches!(card.card_type, CardType::Foundation(_)));
+    let visibility = if visible { Visibility::Inherited } else { Visibility::Hidden };
+    for mut current in query.iter_mut() {
+        current.set_if_neq(visibility);
+    }
+}
+
+/// ゲームクリア時処理
+fn game_clear_system(
+    mut commands: Commands,
+    query: Query<Entity, With<GameClear>>,
+    query_auto_complete: Query<Entity, With<AutoComplete>>,
+) {
+    for entity in query.iter() {
+        for auto_complete in query_auto_complete.iter() {
+            commands.entity(auto_complete).despawn();
         }
+        commands.trigger(GameClearEvent);
 
         commands.entity(entity).despawn();
     }
 }
 
-/// 山札から引いたカードの補充
-fn fill_waste_system(
+/// 手詰まり時処理
+fn game_over_system(
     mut commands: Commands,
-    mut card_list: ResMut<CardList>,
-    query_fill_waste: Query<Entity, With<FillWaste>>,
+    card_list: Res<CardList>,
+    draw: Res<DrawRule>,
+    game: Res<GameKind>,
+    options: Res<CurrentOptions>,
+    stock_recycle: Res<StockRecycle>,
+    query_stack_info: Query<(), With<StackInfo>>,
+    query_auto_complete: Query<(), With<AutoComplete>>,
 ) {
-    for entity in query_fill_waste.iter() {
-        if let Some(mut waste_cards) = card_list.get_waste_cards_mut() {
-            waste_cards.reverse();
-            waste_cards.truncate(MAX_WASTES as usize);
-            waste_cards.reverse();
+    // 山札を戻せる回数に制限があるときだけ、戻せなくなった時点で判定する
+    if stock_recycle.limit.is_none()
+        || !query_stack_info.is_empty()
+        || !query_auto_complete.is_empty()
+        || card_list.0.iter().any(|card| card.dragging) {
+        return;
+    }
 
-            let mut order = 0;
-            for card in waste_cards.iter_mut() {
-                card.order = order;
-                card.clickable = false;
+    if game.ruleset().is_dead_end(&card_list, &stock_recycle, &draw, &options.0) {
+        commands.trigger(GameOverEvent);
+    }
+}
 
-                let mut position = POSITION_WASTE;
-                position.y -= order as f32 * OFFSET_WASTE_Y;
-                (card.dst_position.x, card.dst_position.y) = (position.x, position.y);
+/// 保存したゲームの再開処理
+fn on_continue(
+    _trigger: Trigger<ContinueEvent>,
+    mut commands: Commands,
+    mut card_list: ResMut<CardList>,
+    mut play_board: ResMut<PlayBoard>,
+    mut deal_seed: ResMut<DealSeed>,
+    mut stock_recycle: ResMut<StockRecycle>,
+    mut draw: ResMut<DrawRule>,
+    saved_game: Res<SavedGame>,
+    game_textures: Res<GameTextures>,
+    card_atlas: Res<CardAtlas>,
+    query_auto_complete: Query<Entity, With<AutoComplete>>,
+    query_play_safe: Query<Entity, With<PlaySafe>>,
+) {
+    reset_play_state(&mut commands, &query_auto_complete, &query_play_safe);
+
+    // 保存したゲームを始めたときの設定で続ける
+    let options = saved_game.options;
+    let ruleset = options.game.ruleset();
+    deal_seed.seed = saved_game.seed;
+    commands.insert_resource(options.game);
+    commands.insert_resource(CurrentOptions(options));
+    commands.insert_resource(ruleset.scoring(&options));
+    *draw = saved_game.draw;
+    stock_recycle.limit = ruleset.max_recycle(&saved_game.draw, &options);
+    resize_card_list(&mut commands, &mut card_list, &game_textures, &card_atlas, options.game);
+
+    for (card, saved_card) in card_list.0.iter_mut().zip(saved_game.cards.iter()) {
+        card.card_suit = saved_card.card_suit;
+        card.card_number = saved_card.card_number;
+        card.deck = saved_card.deck;
+        card.card_type = CardType::Stock;
+
+        // 山札の位置から配置する
+        let position = get_slot_position(options.game, CardType::Stock).extend(saved_card.order as f32);
+        card.dst_position = position;
+        commands.entity(card.entity).insert((
+            Transform::from_translation(position),
+            Visibility::Visible,
+        ));
 
-                order += 1;
+        commands.spawn(UpdateSprite {
+            target: card.entity,
+        });
+    }
+
+    let snapshot = Snapshot {
+        cards: saved_game.cards.iter().map(|card| {
+            CardLocation {
+                card_type: card.card_type,
+                order: card.order,
+                facedown: card.facedown,
+                clickable: card.clickable,
             }
+        }).collect(),
+        score: saved_game.score,
+        movement: saved_game.movement,
+        num_recycle: saved_game.num_recycle,
+    };
+    restore_snapshot(&mut commands, &mut card_list, &mut stock_recycle, options.game, None, &snapshot);
+
+    // 盤面を使うゲームは再開した状態から盤面を作る
+    play_board.0 = ruleset.board(&card_list, &draw, &stock_recycle);
+}
+
+/// 「新しいゲーム」クリック時処理
+fn on_click_new_game(
+    click: Trigger<Pointer<Click>>,
+    state: Res<State<GameState>>,
+    mut commands: Commands,
+) {
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    if *state == GameState::Play {
+        commands.trigger(NewGameEvent);
+    }
+}
+
+/// 組札に移しても困らないカードをすべて移す（右クリック、組札のダブルクリック）
+fn on_play_safe(
+    _trigger: Trigger<PlaySafeEvent>,
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    query_play_safe: Query<(), With<PlaySafe>>,
+    query_auto_complete: Query<(), With<AutoComplete>>,
+) {
+    if *state == GameState::Play && query_play_safe.is_empty() && query_auto_complete.is_empty() {
+        commands.spawn(PlaySafe);
+    }
+}
+
+/// 「自動完了」クリック時処理
+fn on_click_auto_complete(
+    click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    query_auto_complete: Query<(), With<AutoComplete>>,
+) {
+    if click.button != PointerButton::Primary {
+        return;
+    }
 
-            if let Some(card) = waste_cards.last_mut() {
-                card.clickable = true;
-            }
-        }
-        commands.entity(entity).despawn();
+    if *state == GameState::Play && query_auto_complete.is_empty() {
+        commands.spawn(AutoComplete { num_recycle: 0 });
     }
 }
 
-/// 場札の表示位置の調整
-fn adjust_pile_system(
+/// 「元に戻す」クリック時処理
+fn on_click_undo(
+    click: Trigger<Pointer<Click>>,
     mut commands: Commands,
-    mut card_list: ResMut<CardList>,
-    query: Query<(Entity, &AdjustPile)>,
 ) {
-    for (entity, adjustpile) in query.iter() {
-        let num_facedown = card_list.num_facedown(adjustpile.index);
-        let num_faceup = card_list.num_faceup(adjustpile.index);
-
-        if let Some(cards) = card_list.get_pile_cards_mut(adjustpile.index) {
-            for card in cards {
-                let pos = calc_pile_position(
-                    adjustpile.index,
-                    card.order,
-                    num_facedown,
-                    num_faceup
-                );
-                card.dst_position = pos;
-            }
-        }
-        commands.entity(entity).despawn();
+    if click.button != PointerButton::Primary {
+        return;
     }
+
+    commands.trigger(UndoEvent);
 }
 
-/// カード表示順の更新
-fn update_z_system(
+/// 「ヒント」クリック時処理
+fn on_click_hint(
+    click: Trigger<Pointer<Click>>,
     mut commands: Commands,
-    card_list: ResMut<CardList>,
-    mut query_transform: Query<&mut Transform, With<Card>>,
-    query_update_z: Query<(Entity, &UpdateZ)>,
 ) {
-    for (entity, update_z) in query_update_z.iter() {
-        if let Some(card) = card_list.get(update_z.target) {
-            if let Ok(mut transform) = query_transform.get_mut(card.entity) {
-                transform.translation.z = update_z.value;
-            }
-        }
-        commands.entity(entity).despawn();
+    if click.button != PointerButton::Primary {
+        return;
     }
+
+    commands.trigger(HintEvent);
 }
 
-/// カード画像の更新
-fn update_sprite_system(
+/// 「やり直す」クリック時処理
+fn on_click_redo(
+    click: Trigger<Pointer<Click>>,
     mut commands: Commands,
-    card_list: ResMut<CardList>,
-    mut query_sprite: Query<&mut Sprite, With<Card>>,
-    query_update_sprite: Query<(Entity, &UpdateSprite)>,
 ) {
-    for (entity, update_sprite) in query_update_sprite.iter() {
-        if let Some(card) = card_list.get(update_sprite.target) {
-            if let Ok(mut sprite) = query_sprite.get_mut(card.entity) {
-                if card.facedown {
-                    facedown_card(sprite.texture_atlas.as_mut());
-                } else {
-                    faceup_card(card.card_suit, card.card_number, sprite.texture_atlas.as_mut());
-                }
-            }
-        }
-        commands.entity(entity).despawn();
+    if click.button != PointerButton::Primary {
+        return;
     }
+
+    commands.trigger(RedoEvent);
 }
 
-/// ゲームクリア時処理
-fn game_clear_system(
+/// 元に戻す処理
+fn on_undo(
+    _trigger: Trigger<UndoEvent>,
+    state: Res<State<GameState>>,
     mut commands: Commands,
     mut card_list: ResMut<CardList>,
-    query: Query<Entity, With<GameClear>>,
+    mut history: ResMut<History>,
+    mut play_board: ResMut<PlayBoard>,
+    mut stock_recycle: ResMut<StockRecycle>,
+    mut run: ResMut<PeakRun>,
+    game: Res<GameKind>,
+    score: Res<Score>,
+    movement: Res<Movement>,
+    query_auto_complete: Query<Entity, With<AutoComplete>>,
+    query_play_safe: Query<Entity, With<PlaySafe>>,
 ) {
-    for entity in query.iter() {
-        for card in card_list.0.iter_mut() {
-            card.card_type = CardType::Foundation(card.card_suit);
-            card.order = card.card_number as i32;
-
-            let position = POSITION_FOUNDATIONS[card.card_suit as usize];
-            card.dst_position = Vec3::new(position.x, position.y, card.order as f32);
+    if *state != GameState::Play || card_list.0.iter().any(|card| card.dragging) {
+        return;
+    }
 
-            commands.spawn(UpdateZ {
-                target: card.entity,
-                value: card.card_number as f32,
-            });
-        }
-        commands.trigger(GameClearEvent);
+    // 続けて重ねた枚数は数え直す
+    run.0 = 0;
 
+    // 元に戻したら自動完了を中断する
+    for entity in query_auto_complete.iter().chain(query_play_safe.iter()) {
         commands.entity(entity).despawn();
     }
+
+    let current = Snapshot::new(&card_list, score.0, movement.0, &stock_recycle);
+    if let Some(entry) = history.undo(current) {
+        // 盤面を使うゲームは盤面の移動を元に戻す
+        let board = play_board.0.as_mut().zip(entry.applied).map(|(board, applied)| {
+            board.revert(&applied);
+            &*board
+        });
+        restore_snapshot(&mut commands, &mut card_list, &mut stock_recycle, *game, board, &entry.before);
+        for step in entry.steps.iter().rev() {
+            commands.trigger(RestoreStepEvent(*step));
+        }
+    }
 }
 
-/// 「新しいゲーム」クリック時処理
-fn on_click_new_game(
-    click: Trigger<Pointer<Click>>,
+/// やり直す処理
+fn on_redo(
+    _trigger: Trigger<RedoEvent>,
     state: Res<State<GameState>>,
     mut commands: Commands,
+    mut card_list: ResMut<CardList>,
+    mut history: ResMut<History>,
+    mut play_board: ResMut<PlayBoard>,
+    mut stock_recycle: ResMut<StockRecycle>,
+    mut run: ResMut<PeakRun>,
+    game: Res<GameKind>,
 ) {
-    if click.button != PointerButton::Primary {
+    if *state != GameState::Play || card_list.0.iter().any(|card| card.dragging) {
         return;
     }
 
-    if *state == GameState::Play {
-        commands.trigger(NewGameEvent);
+    // 続けて重ねた枚数は数え直す
+    run.0 = 0;
+
+    if let Some(entry) = history.redo() {
+        if let Some(after) = &entry.after {
+            // 盤面を使うゲームは盤面に移動を適用し直す
+            let board = play_board.0.as_mut().zip(entry.applied).map(|(board, applied)| {
+                board.apply(applied.mv);
+                &*board
+            });
+            restore_snapshot(&mut commands, &mut card_list, &mut stock_recycle, *game, board, after);
+        }
+        for step in entry.steps.iter() {
+            commands.trigger(RestoreStepEvent(*step));
+        }
     }
 }
 
@@ -807,265 +1539,308 @@ fn on_click_new_game(
 fn on_click_stock_base(
     click: Trigger<Pointer<Click>>,
     mut commands: Commands,
-    mut card_list: ResMut<CardList>,
 ) {
     if click.button != PointerButton::Primary {
         return;
     }
 
-    // すべて山札に戻す
-    if let Some(waste_cards) = card_list.get_waste_cards_mut() {
-        let mut count = 0;
-        for card in waste_cards {
-            card.card_type = CardType::Stock;
-            card.clickable = true;
-            card.order = count;
+    commands.trigger(RecycleStockEvent);
+}
 
-            card.facedown = true;
-            commands.spawn(UpdateSprite {
-                target: card.entity,
-            });
-            
-            let pos = POSITION_STOCK;
-            card.dst_position = Vec3::new(pos.x, pos.y, count as f32);
-            
-            count += 1;
+/// 山札を戻す処理（山札ベースのクリック、キーボード、自動完了）
+fn on_recycle_stock(
+    _trigger: Trigger<RecycleStockEvent>,
+    mut commands: Commands,
+    mut card_list: ResMut<CardList>,
+    mut history: ResMut<History>,
+    mut play_board: ResMut<PlayBoard>,
+    scoring: Res<ScoringMode>,
+    game: Res<GameKind>,
+    mut stock_recycle: ResMut<StockRecycle>,
+    score: Res<Score>,
+    movement: Res<Movement>,
+) {
+    // 戻せる回数を使い切った
+    if stock_recycle.limit.is_some_and(|limit| stock_recycle.count >= limit) {
+        return;
+    }
+
+    let before = Snapshot::new(&card_list, score.0, movement.0, &stock_recycle);
+    let recycled = match play_board.0.as_mut() {
+        Some(board) => match board.apply(Move::WasteToStock) {
+            Some(applied) => {
+                card_list.sync_board(board);
+                commit_board_move(&mut commands, &mut card_list, &mut history, *game, *scoring, before, applied)
+            }
+            None => false,
+        },
+        None => {
+            let steps = card_list.return_waste_to_stock();
+            commit_move(&mut commands, &mut card_list, &mut history, *game, *scoring, before, &steps)
         }
+    };
+    if recycled {
+        stock_recycle.count += 1;
+    }
+}
 
-        commands.trigger(MoveOneStepEvent(MoveStep::WasteToStock));
-        commands.trigger(AddScoreEvent(get_score(MoveStep::WasteToStock)));
+/// 山札をめくる処理（山札のクリック、キーボード、自動完了）
+fn on_turn_stock(
+    _trigger: Trigger<TurnStockEvent>,
+    mut commands: Commands,
+    mut card_list: ResMut<CardList>,
+    mut history: ResMut<History>,
+    mut play_board: ResMut<PlayBoard>,
+    mut run: ResMut<PeakRun>,
+    draw: Res<DrawRule>,
+    scoring: Res<ScoringMode>,
+    game: Res<GameKind>,
+    stock_recycle: Res<StockRecycle>,
+    score: Res<Score>,
+    movement: Res<Movement>,
+) {
+    // 山札をめくると続けて重ねた枚数は数え直す（トライピークス、ゴルフ）
+    run.0 = 0;
+
+    let before = Snapshot::new(&card_list, score.0, movement.0, &stock_recycle);
+    match play_board.0.as_mut() {
+        Some(board) => {
+            if let Some(applied) = board.apply(Move::StockToWaste) {
+                card_list.sync_board(board);
+                commit_board_move(&mut commands, &mut card_list, &mut history, *game, *scoring, before, applied);
+            }
+        }
+        None => {
+            let steps = game.ruleset().turn_stock(&mut card_list, &draw);
+            commit_move(&mut commands, &mut card_list, &mut history, *game, *scoring, before, &steps);
+        }
     }
 }
 
 /// カードクリック時処理
 fn on_click_card(
     click: Trigger<Pointer<Click>>,
-    commands: Commands,
-    card_list: ResMut<CardList>,
-    difficulty: Res<GameDifficulty>,
+    mut commands: Commands,
+    card_list: Res<CardList>,
+    mut foundation_click: ResMut<FoundationClick>,
 ) {
-    if click.button != PointerButton::Primary {
+    match click.button {
+        PointerButton::Primary => {}
+        PointerButton::Secondary => {
+            commands.trigger(PlaySafeEvent);
+            return;
+        }
+        _ => return,
+    }
+
+    // 組札のカードはダブルクリックかどうかを確かめてから処理する
+    if card_list.get(click.entity()).is_some_and(|card| matches!(card.card_type, CardType::Foundation(_))) {
+        click_foundation(&mut commands, &mut foundation_click, click.entity());
         return;
     }
-    
-    if let Some(card) = card_list.get(click.entity()) {
-        if card.clickable {
-            match card.card_type {
-                CardType::Stock => on_click_stock(commands, card_list, difficulty),
-                CardType::Waste => on_click_waste(commands, card_list, click.entity()),
-                CardType::Pile(_) => on_click_pile(commands, card_list, click.entity()),
-                CardType::Foundation(_) => on_click_foundation(commands, card_list, click.entity()),
+
+    commands.trigger_targets(SelectCardEvent, click.entity());
+}
+
+/// 組札のクリック時処理（ダブルクリックなら組札に移しても困らないカードをすべて移す）
+fn click_foundation(
+    commands: &mut Commands,
+    foundation_click: &mut FoundationClick,
+    entity: Entity,
+) {
+    match foundation_click.0.take() {
+        Some((pending, _)) if pending == entity => {
+            commands.trigger(PlaySafeEvent);
+        }
+        pending => {
+            // 別の組札をクリックしたときは、前のクリックをすぐに処理する
+            if let Some((pending, _)) = pending {
+                commands.trigger_targets(SelectCardEvent, pending);
             }
+            foundation_click.0 = Some((entity, Timer::from_seconds(DOUBLE_CLICK_SECONDS, TimerMode::Once)));
         }
     }
 }
 
-/// 山札クリック時処理
-fn on_click_stock(
+/// カード選択時処理（クリック、キーボード）
+fn on_select_card(
+    select: Trigger<SelectCardEvent>,
     mut commands: Commands,
     mut card_list: ResMut<CardList>,
-    difficulty: Res<GameDifficulty>,
+    mut history: ResMut<History>,
+    mut selection: ResMut<CardSelection>,
+    mut run: ResMut<PeakRun>,
+    cycle: ResMut<AutoMoveCycle>,
+    scoring: Res<ScoringMode>,
+    game: Res<GameKind>,
+    options: Res<GameOptions>,
+    current_options: Res<CurrentOptions>,
+    stock_recycle: Res<StockRecycle>,
+    score: Res<Score>,
+    movement: Res<Movement>,
 ) {
-    let mut num_turn_cards =
-        if let Some(turn_cards) = card_list.get_turn_cards_mut(num_turn_to_waste(*difficulty)) {
-            turn_cards.len() as u32
-        } else {
-            0
-        };
-    let num_waste_cards =
-        if let Some(cards) = card_list.get_waste_cards_mut() {
-            cards.len() as u32
-        } else {
-            0
-        };
-
-    // 山札を難易度に応じて1枚か3枚めくる
-    if let Some(mut waste_top) = card_list.get_waste_cards_mut() {
-        // すでにめくられたカードを奥に移動
-        waste_top.reverse();
-        waste_top.truncate(MAX_WASTES as usize);
-        waste_top.reverse();
-
-        for card in waste_top.iter_mut() {
-            card.clickable = false;
-        }
-
-        let mut count = 0;
-        let num_waste_top = waste_top.len() as i32;
-        if num_waste_top + num_turn_cards as i32 > MAX_WASTES as i32 {
-            for card in waste_top.iter_mut() {
-                let mut order = count - (num_waste_top + num_turn_cards as i32 - MAX_WASTES as i32);
-                order = order.clamp(0, MAX_WASTES as i32 - 1);
-                card.order = order;
-    
-                let mut pos = POSITION_WASTE;
-                pos.y -= order as f32 * OFFSET_WASTE_Y;
-                card.dst_position.y = pos.y;
-    
-                count += 1;
-            }
-        }
+    let ruleset = game.ruleset();
+    let entity = select.entity();
+
+    // 選んでから置く設定では、移動先をクリックするまで動かさない（山札はそのままめくる）
+    if options.click_mode == ClickMode::SelectThenPlace
+        && ruleset.can_select_then_place()
+        && card_list.get(entity).is_some_and(|card| card.card_type != CardType::Stock) {
+        select_or_place(commands, card_list, selection, *game, entity);
+        return;
     }
 
-    // めくったカードを移動
-    if let Some(mut turn_cards) = card_list.get_turn_cards_mut(num_turn_to_waste(*difficulty)) {
-        num_turn_cards = turn_cards.len() as u32;
-        let mut count = 0;
-        for card in turn_cards.iter_mut() {
-            card.card_type = CardType::Waste;
-            card.clickable = false;
-            
-            let mut order = num_waste_cards.clamp(0, MAX_WASTES) as i32;
-            if order + num_turn_cards as i32 - 1 > MAX_WASTES as i32 - 1 {
-                order -= order + num_turn_cards as i32 - 1 - (MAX_WASTES as i32 - 1);
+    let Some(card) = card_list.get(entity).filter(|card| card.clickable) else {
+        return;
+    };
+    let action = ruleset.click_action(card);
+    let before = Snapshot::new(&card_list, score.0, movement.0, &stock_recycle);
+    match action {
+        ClickAction::TurnStock => commands.trigger(TurnStockEvent),
+        ClickAction::PlayToWaste => {
+            let steps = ruleset.play_to_waste(&mut card_list, entity, &current_options);
+            if commit_move(&mut commands, &mut card_list, &mut history, *game, *scoring, before, &steps) {
+                run.0 += 1;
+                commands.trigger(AddScoreEvent(ruleset.run_score(run.0)));
             }
-            order += count;
-            card.order = order;
-            
-            let mut pos = POSITION_WASTE;
-            pos.y -= order as f32 * OFFSET_WASTE_Y;
-            (card.dst_position.x, card.dst_position.y) = (pos.x, pos.y);
-
-            let z = num_waste_cards as f32 + num_turn_cards as f32 + order as f32;
-            card.dst_position = Vec3::new(pos.x, pos.y, z);
-
-            commands.spawn(UpdateZ {
-                target: card.entity,
-                value: z,
-            });
-
-            card.facedown = false;
-            commands.spawn(UpdateSprite {
-                target: card.entity,
-            });
-
-            count += 1;
         }
-
-        if let Some(card) = turn_cards.last_mut() {
-            card.clickable = true;
+        ClickAction::RemovePair => {
+            // 1枚で取り除けなければ、選んでおいたカードと合わせて取り除く
+            let selected = selection.0.take();
+            let mut steps = ruleset.remove_cards(&mut card_list, &[entity]);
+            if steps.is_empty() && selected != Some(entity) {
+                if let Some(selected) = selected {
+                    steps = ruleset.remove_cards(&mut card_list, &[selected, entity]);
+                }
+                // 取り除けなかったときは、クリックしたカードを選ぶ
+                if steps.is_empty() {
+                    selection.0 = Some(entity);
+                    return;
+                }
+            }
+            commit_move(&mut commands, &mut card_list, &mut history, *game, *scoring, before, &steps);
         }
-
-        commands.trigger(MoveOneStepEvent(MoveStep::StockToWaste));
-        commands.trigger(AddScoreEvent(get_score(MoveStep::StockToWaste)));
+        ClickAction::AutoMove => on_click_auto_move(commands, card_list, cycle, *game, entity),
+        ClickAction::None => {}
     }
 }
 
-/// 山札から引いたカードのクリック時処理
-fn on_click_waste(
+/// 動かせるカードのクリック時処理（移動先の候補に動かす）
+fn on_click_auto_move(
     mut commands: Commands,
     card_list: ResMut<CardList>,
+    mut cycle: ResMut<AutoMoveCycle>,
+    game: GameKind,
     card: Entity,
 ) {
-    if let Some(card) = card_list.get(card) {
-        if card.dragging {
-            return;
-        }
+    let Some(card) = card_list.get(card) else {
+        return;
+    };
+    if card.dragging {
+        return;
+    }
 
-        if card_list.can_stack_foundation(card) {
-            commands.spawn(StackInfo {
-                target: card.entity,
-                dst: CardType::Foundation(card.card_suit),
-                order: card.card_number as i32,
-            });
-            commands.spawn(UpdateZ {
-                target: card.entity,
-                value: DRAG_CARD_Z,
-            });
-        } else {
-            for index in 0..NUM_PILES {
-                if let Some(order) = card_list.can_stack_pile(index as u32, card) {
-                    commands.spawn(StackInfo {
-                        target: card.entity,
-                        dst: CardType::Pile(index as u32),
-                        order: order,
-                    });
-                    commands.spawn(UpdateZ {
-                        target: card.entity,
-                        value: DRAG_CARD_Z,
-                    });
-                    break;
-                }
-            }
+    // 前回動かしたカードを続けてクリックしたときは、次の候補に移す
+    let repeated = cycle.target == Some(card.entity) && cycle.dsts.get(cycle.index) == Some(&card.card_type);
+    let start = if repeated {
+        cycle.index + 1
+    } else {
+        *cycle = AutoMoveCycle {
+            target: Some(card.entity),
+            dsts: game.ruleset().auto_move_dsts(&card_list, card),
+            index: 0,
+        };
+        0
+    };
+
+    let num_dsts = cycle.dsts.len();
+    let next = (0..num_dsts).map(|offset| (start + offset) % num_dsts).find_map(|index| {
+        let dst = cycle.dsts[index];
+        if dst == card.card_type {
+            return None;
         }
-    }
+        game.ruleset().stack_order(&card_list, card, dst).map(|order| (index, dst, order))
+    });
+
+    let Some((index, dst, order)) = next else {
+        return;
+    };
+    cycle.index = index;
+    commands.spawn(StackInfo {
+        target: card.entity,
+        dst,
+        order,
+    });
+    raise_cards(&mut commands, &card_list, card);
 }
 
-/// 場札クリック時処理
-fn on_click_pile(
+/// カードを選ぶ、または選んだカードをクリックしたカードの置き場に置く
+fn select_or_place(
     mut commands: Commands,
     card_list: ResMut<CardList>,
+    mut selection: ResMut<CardSelection>,
+    game: GameKind,
     card: Entity,
 ) {
-    if let Some(card) = card_list.get(card) {
-        if card.dragging {
+    let Some(card) = card_list.get(card) else {
+        return;
+    };
+    if card.dragging {
+        return;
+    }
+
+    // 選んだカードがあれば、クリックしたカードの置き場に置く（同じカードのときは選択をやめる）
+    if let Some(selected) = selection.0.take().and_then(|entity| card_list.get(entity)) {
+        if selected.entity == card.entity {
             return;
         }
-        
-        if card_list.can_stack_foundation(card) {
-            if let None = card_list.get_connected_cards(card.entity) {
-                commands.spawn(StackInfo {
-                    target: card.entity,
-                    dst: CardType::Foundation(card.card_suit),
-                    order: card.card_number as i32,
-                });
-                commands.spawn(UpdateZ {
-                    target: card.entity,
-                    value: DRAG_CARD_Z,
-                });
-                return;
-            }
-        }
-        for index in 0..NUM_PILES {
-            if let Some(order) = card_list.can_stack_pile(index as u32, card) {
-                commands.spawn(StackInfo {
-                    target: card.entity,
-                    dst: CardType::Pile(index as u32),
-                    order: order,
-                });
-                commands.spawn(UpdateZ {
-                    target: card.entity,
-                    value: DRAG_CARD_Z,
-                });
-                if let Some(cards) = card_list.get_connected_cards(card.entity) {
-                    for card in cards {
-                        commands.spawn(UpdateZ {
-                            target: card.entity,
-                            value: DRAG_CARD_Z + card.order as f32,
-                        });
-                    }
-                }
-                break;
-            }
+        if selected.card_type != card.card_type
+            && stack_card(&mut commands, &card_list, game, selected, [card.card_type]) {
+            raise_cards(&mut commands, &card_list, selected);
+            return;
         }
     }
+
+    // 置けなかったときは、クリックしたカードを選び直す
+    if can_drag(&card_list, card, game) {
+        selection.0 = Some(card.entity);
+    }
 }
 
-/// 組札クリック時処理
-fn on_click_foundation(
+/// 台と置き場ベースのクリック時処理（選んだカードを空いた置き場に置く、右クリックで組札に移しても困らないカードをすべて移す）
+fn on_click_table(
+    click: Trigger<Pointer<Click>>,
     mut commands: Commands,
-    card_list: ResMut<CardList>,
-    card: Entity,
+    card_list: Res<CardList>,
+    mut selection: ResMut<CardSelection>,
+    mut foundation_click: ResMut<FoundationClick>,
+    game: Res<GameKind>,
+    query_base: Query<&SlotBase>,
 ) {
-    if let Some(card) = card_list.get(card) {
-        if card.dragging {
+    match click.button {
+        PointerButton::Primary => {}
+        PointerButton::Secondary => {
+            commands.trigger(PlaySafeEvent);
             return;
         }
-        
-        for index in 0..NUM_PILES {
-            if let Some(order) = card_list.can_stack_pile(index as u32, card) {
-                commands.spawn(StackInfo {
-                    target: card.entity,
-                    dst: CardType::Pile(index as u32),
-                    order: order,
-                });
-                commands.spawn(UpdateZ {
-                    target: card.entity,
-                    value: DRAG_CARD_Z,
-                });
-                break;
-            }
+        _ => return,
+    }
+
+    let Some(card) = selection.0.take().and_then(|entity| card_list.get(entity)) else {
+        // 空いた組札ベースはダブルクリックのみ
+        if let Ok(SlotBase::Foundation(_)) = query_base.get(click.entity()) {
+            click_foundation(&mut commands, &mut foundation_click, click.entity());
         }
+        return;
+    };
+    let Some(position) = click.hit.position.map(|position| position.truncate()) else {
+        return;
+    };
+    let dsts = get_drop_areas(*game).into_iter().filter(|(_, area)| {
+        area.closest_point(position) == position
+    }).map(|(dst, _)| dst);
+    if stack_card(&mut commands, &card_list, *game, card, dsts) {
+        raise_cards(&mut commands, &card_list, card);
     }
 }
 
@@ -1073,18 +1848,17 @@ fn on_click_foundation(
 fn on_drag_start(
     drag: Trigger<Pointer<DragStart>>,
     mut card_list: ResMut<CardList>,
+    game: Res<GameKind>,
 ) {
     if drag.button != PointerButton::Primary {
         return;
     }
-    
+
+    if !card_list.get(drag.entity()).is_some_and(|card| can_drag(&card_list, card, *game)) {
+        return;
+    }
     if let Some(card) = card_list.get_mut(drag.entity()) {
-        if !card.clickable || card.card_type == CardType::Stock {
-            return;
-        }
-        if card.clickable {
-            card.prev_position = card.dst_position;
-        }
+        card.prev_position = card.dst_position;
     }
 
     if let Some(cards) = card_list.get_connected_cards_mut(drag.entity()) {
@@ -1098,6 +1872,7 @@ fn on_drag_start(
 fn on_drag(
     drag: Trigger<Pointer<Drag>>,
     mut card_list: ResMut<CardList>,
+    game: Res<GameKind>,
     mut query: Query<&mut Transform, With<Card>>,
 ) {
     if drag.button != PointerButton::Primary {
@@ -1106,17 +1881,20 @@ fn on_drag(
     
     let mut card_type = CardType::Stock;
     if let Some(card) = card_list.get(drag.entity()) {
-        if !card.clickable || card.card_type == CardType::Stock {
+        if !can_drag(&card_list, card, *game) {
             return;
         }
         card_type = card.card_type;
     }
 
+    // 盤面を縮小しているときは画面上の移動量を盤面の移動量に直す
+    let scale = game.ruleset().table_scale();
+
     if let Some(card) = card_list.get_mut(drag.entity()) {
         if let Ok(mut transform) = query.get_mut(card.entity) {
             let mut position = transform.translation;
-            position.x += drag.delta.x;
-            position.y -= drag.delta.y;
+            position.x += drag.delta.x * scale;
+            position.y -= drag.delta.y * scale;
             position.z = DRAG_CARD_Z + card.order as f32;
             transform.translation = position;
             card.dst_position = position;
@@ -1131,8 +1909,8 @@ fn on_drag(
             for card in cards {
                 if let Ok(mut transform) = query.get_mut(card.entity) {
                     let mut position = transform.translation;
-                    position.x += drag.delta.x;
-                    position.y -= drag.delta.y;
+                    position.x += drag.delta.x * scale;
+                    position.y -= drag.delta.y * scale;
                     position.z = DRAG_CARD_Z + card.order as f32;
                     transform.translation = position;
                     card.dst_position = position;
@@ -1147,6 +1925,7 @@ fn on_drag_end(
     drag: Trigger<Pointer<DragEnd>>,
     mut commands: Commands,
     mut card_list: ResMut<CardList>,
+    game: Res<GameKind>,
     query: Query<&Transform, With<Card>>,
 ) {
     if drag.button != PointerButton::Primary {
@@ -1157,7 +1936,7 @@ fn on_drag_end(
     let mut card_type = CardType::Stock;
 
     if let Some(card) = card_list.get(drag.entity()) {
-        if !card.clickable || card.card_type == CardType::Stock {
+        if !can_drag(&card_list, card, *game) {
             return;
         }
 
@@ -1166,83 +1945,11 @@ fn on_drag_end(
         if let Ok(transform) = query.get(card.entity) {
             let card_aabb = Aabb2d::new(transform.translation.truncate(), CARD_SIZE / 2.);
 
-            match card.card_type {
-                CardType::Stock => {}
-
-                // 山札からめくったカードの移動
-                CardType::Waste => {
-                    if card_aabb.intersects(&FOUNDATION_DROP_AREA[card.card_suit as usize])
-                    && card_list.can_stack_foundation(card) {
-                        commands.spawn(StackInfo {
-                            target: card.entity,
-                            dst: CardType::Foundation(card.card_suit),
-                            order: card.card_number as i32,
-                        });
-                        can_stack = true;
-                    } else {
-                        for index in 0..NUM_PILES {
-                            if card_aabb.intersects(&PILE_DROP_AREA[index]) {
-                                if let Some(order) = card_list.can_stack_pile(index as u32, card) {
-                                    commands.spawn(StackInfo {
-                                        target: card.entity,
-                                        dst: CardType::Pile(index as u32),
-                                        order: order,
-                                    });
-                                    can_stack = true;
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
-
-                // 場札の移動
-                CardType::Pile(_) => {
-                    if card_aabb.intersects(&FOUNDATION_DROP_AREA[card.card_suit as usize]) {
-                        if let None = card_list.get_connected_cards(card.entity) {
-                            if card_list.can_stack_foundation(card) {
-                                commands.spawn(StackInfo {
-                                    target: card.entity,
-                                    dst: CardType::Foundation(card.card_suit),
-                                    order: card.card_number as i32,
-                                });
-                                can_stack = true;
-                            }
-                        }
-                    } else {
-                        for index in 0..NUM_PILES {
-                            if card_aabb.intersects(&PILE_DROP_AREA[index]) {
-                                if let Some(order) = card_list.can_stack_pile(index as u32, card) {
-                                    commands.spawn(StackInfo {
-                                        target: card.entity,
-                                        dst: CardType::Pile(index as u32),
-                                        order: order,
-                                    });
-                                    can_stack = true;
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
-                
-                // 組札の移動
-                CardType::Foundation(_) => {
-                    for index in 0..NUM_PILES {
-                        if card_aabb.intersects(&PILE_DROP_AREA[index]) {
-                            if let Some(order) = card_list.can_stack_pile(index as u32, card) {
-                                commands.spawn(StackInfo {
-                                    target: card.entity,
-                                    dst: CardType::Pile(index as u32),
-                                    order: order,
-                                });
-                                can_stack = true;
-                                break;
-                            }
-                        }
-                    }
-                }
-            }
+            // 重なった置き場のうち、最初に置けるところに移動
+            let dsts = get_drop_areas(*game).into_iter().filter(|(_, area)| {
+                card_aabb.intersects(area)
+            }).map(|(dst, _)| dst);
+            can_stack = stack_card(&mut commands, &card_list, *game, card, dsts);
         }
     }
 
@@ -1264,6 +1971,85 @@ fn on_drag_end(
     }
 }
 
+/// 移動先を指定したときの処理（キーボード）
+fn on_stack_card(
+    stack: Trigger<StackCardEvent>,
+    mut commands: Commands,
+    card_list: Res<CardList>,
+    game: Res<GameKind>,
+) {
+    let Some(card) = card_list.get(stack.entity()) else {
+        return;
+    };
+    if card.dragging || !can_drag(&card_list, card, *game) {
+        return;
+    }
+
+    // ドラッグで置いたときと同じく、候補のうち最初に置けるところに移動
+    if stack_card(&mut commands, &card_list, *game, card, stack.event().0.iter().copied()) {
+        raise_cards(&mut commands, &card_list, card);
+    }
+}
+
+/// 移動するカードとつながったカードを手前に表示
+fn raise_cards(
+    commands: &mut Commands,
+    card_list: &CardList,
+    card: &CardInfo,
+) {
+    commands.spawn(UpdateZ {
+        target: card.entity,
+        value: DRAG_CARD_Z,
+    });
+    if let Some(cards) = card_list.get_connected_cards(card.entity) {
+        for card in cards {
+            commands.spawn(UpdateZ {
+                target: card.entity,
+                value: DRAG_CARD_Z + card.order as f32,
+            });
+        }
+    }
+}
+
+/// 移動先の候補のうち、最初に置けるところへの移動を登録（置けたかどうかを返す）
+fn stack_card(
+    commands: &mut Commands,
+    card_list: &CardList,
+    game: GameKind,
+    card: &CardInfo,
+    dsts: impl IntoIterator<Item = CardType>,
+) -> bool {
+    let dst = dsts.into_iter().find_map(|dst| {
+        game.ruleset().stack_order(card_list, card, dst).map(|order| (dst, order))
+    });
+
+    let Some((dst, order)) = dst else {
+        return false;
+    };
+    commands.spawn(StackInfo {
+        target: card.entity,
+        dst,
+        order,
+    });
+    true
+}
+
+/// ドラッグできるカードかどうかを取得
+fn can_drag(card_list: &CardList, card: &CardInfo, game: GameKind) -> bool {
+    card.clickable
+        && card.card_type != CardType::Stock
+        && game.ruleset().can_drag(card_list, card)
+}
+
+/// ドロップできる置き場と範囲の取得処理
+fn get_drop_areas(game: GameKind) -> Vec<(CardType, Aabb2d)> {
+    // 置けるかどうかはルールで判定する
+    game.ruleset().drop_slots().into_iter().map(|slot| match slot {
+        CardType::Pile(index) => (slot, get_pile_drop_area(game, index)),
+        _ => (slot, Aabb2d::new(get_slot_position(game, slot), CARD_SIZE / 2.)),
+    }).collect()
+}
+
 /// カード裏向き処理
 fn facedown_card(atlas: Option<&mut TextureAtlas>) {
     if let Some(atlas) = atlas {
@@ -1280,8 +2066,46 @@ fn faceup_card(suit: CardSuit, number: u32, atlas: Option<&mut TextureAtlas>) {
     }
 }
 
+/// カード置き場の表示位置取得処理
+pub fn get_slot_position(game: GameKind, card_type: CardType) -> Vec2 {
+    // 場札の列やフリーセルなどの配置はゲームごとに決める
+    if let Some(position) = game.ruleset().slot_position(card_type) {
+        return position;
+    }
+    match card_type {
+        CardType::Stock => POSITION_STOCK,
+        CardType::Waste => POSITION_WASTE,
+        CardType::Foundation(slot) => POSITION_FOUNDATIONS[slot as usize],
+        _ => Vec2::ZERO,
+    }
+}
+
+/// カードの表示位置取得処理
+fn get_card_position(card_list: &CardList, game: GameKind, card: &CardInfo) -> Vec3 {
+    match card.card_type {
+        CardType::Pile(index) => calc_pile_position(
+            game,
+            index,
+            card.order,
+            card_list.num_facedown(index),
+            card_list.num_faceup(index)
+        ),
+        card_type => get_slot_position(game, card_type).extend(card.order as f32),
+    }
+}
+
+/// 場札のドロップ範囲取得処理
+fn get_pile_drop_area(game: GameKind, pile_index: u32) -> Aabb2d {
+    let position = get_slot_position(game, CardType::Pile(pile_index));
+    Aabb2d {
+        min: Vec2::new(position.x - CARD_SIZE.x / 2., PILE_DROP_AREA_BOTTOM),
+        max: Vec2::new(position.x + CARD_SIZE.x / 2., PILE_DROP_AREA_TOP),
+    }
+}
+
 /// 組札の表示位置計算処理
 fn calc_pile_position(
+    game: GameKind,
     pile_index: u32,
     order: i32,
     num_facedown: u32,
@@ -1290,8 +2114,7 @@ fn calc_pile_position(
     let mut offset_facedown = OFFSET_PILE_Y;
     let mut offset_faceup = OFFSET_PILE_Y;
     let card_height =  CARD_SIZE.y;
-    let pile_drop_area_height =
-        (PILE_DROP_AREA[pile_index as usize].max - PILE_DROP_AREA[pile_index as usize].min).y;
+    let pile_drop_area_height = PILE_DROP_AREA_TOP - PILE_DROP_AREA_BOTTOM;
 
     let mut height_over =
         card_height + (num_facedown + num_faceup) as f32 * OFFSET_PILE_Y - pile_drop_area_height;
@@ -1306,7 +2129,7 @@ fn calc_pile_position(
         }
     }
 
-    let xy = POSITION_PILES[pile_index as usize];
+    let xy = get_slot_position(game, CardType::Pile(pile_index));
     let mut position = Vec3::new(xy.x, xy.y, order as f32);
     for i in 0..(num_facedown + num_faceup) {
         if i == order as u32 {
@@ -1322,54 +2145,65 @@ fn calc_pile_position(
     position
 }
 
-/// ゲームクリア判定
-pub fn is_game_clear(cards: &Vec<CardInfo>, num_turn_to_waste: u32) -> bool {
-    // 裏の場札がある or 山札が残っている
-    for card in cards.iter() {
-        if let CardType::Pile(_) = card.card_type {
-            if card.facedown {
-                return false;
+/// 記録した状態にカードを戻す
+fn restore_snapshot(
+    commands: &mut Commands,
+    card_list: &mut CardList,
+    stock_recycle: &mut StockRecycle,
+    game: GameKind,
+    board: Option<&Board>,
+    snapshot: &Snapshot
+) {
+    stock_recycle.count = snapshot.num_recycle;
+
+    // 盤面を使うゲームはカードの置き場を盤面に合わせる
+    let before: Vec<CardLocation> = card_list.0.iter().map(CardLocation::new).collect();
+    match board {
+        Some(board) => card_list.sync_board(board),
+        None => {
+            for (card, location) in card_list.0.iter_mut().zip(snapshot.cards.iter()) {
+                card.card_type = location.card_type;
+                card.order = location.order;
+                card.facedown = location.facedown;
+                card.clickable = location.clickable;
             }
         }
-        if card.card_type == CardType::Stock {
-            return false;
-        }
-    }
-
-    // 山札からめくったカードの中で操作できないカードがある
-    let num_waste = cards.iter().filter(|card| {
-        card.card_type == CardType::Waste
-    }).count() as u32;
-    if num_waste > MAX_WASTES {
-        return false;
-    }
-    if num_turn_to_waste > 1 && num_waste > 1 {
-        return false;
     }
+    update_cards(commands, card_list, game, &before);
 
-    true
+    commands.trigger(RestoreInformationEvent {
+        score: snapshot.score,
+        movement: snapshot.movement,
+    });
 }
 
-/// スコア取得処理
-fn get_score(move_step: MoveStep) -> i32 {
-    match move_step {
-        MoveStep::StockToWaste => 0,
-        MoveStep::WasteToStock => -100,
-        MoveStep::WasteToPile => 5,
-        MoveStep::WasteToFoundation => 10,
-        MoveStep::PileToPile => 0,
-        MoveStep::PileToFoundation => 15,
-        MoveStep::FoundationToPile => -15,
-        MoveStep::FaceupPile => 5,
-    }
+/// 盤面に適用した移動の手の取得処理
+fn get_board_move_steps(applied: &AppliedMove) -> Vec<MoveStep> {
+    let step = match applied.mv {
+        Move::StockToWaste => MoveStep::StockToWaste,
+        Move::WasteToStock => MoveStep::WasteToStock,
+        Move::WasteToPile(_) => MoveStep::WasteToPile,
+        Move::WasteToFoundation(_) => MoveStep::WasteToFoundation,
+        Move::PileToPile { .. } => MoveStep::PileToPile,
+        Move::PileToFoundation { .. } => MoveStep::PileToFoundation,
+        Move::FoundationToPile { .. } => MoveStep::FoundationToPile,
+    };
+
+    // 場札の裏向きのカードをめくったときは続けて記録する
+    std::iter::once(step).chain(applied.faceup.then_some(MoveStep::FaceupPile)).collect()
 }
 
-/// 山札から引くカード枚数取得処理
-pub fn num_turn_to_waste(difficulty: GameDifficulty) -> u32 {
-    if difficulty == GameDifficulty::Easy {
-        1
-    } else {
-        3
+/// 山札を戻せる回数の取得処理（None は無制限）
+/// ベガス方式は1枚めくりで1周、3枚めくりで3周まで。それ以外の枚数はめくり直しの設定に従う
+pub fn get_max_recycle(
+    num_turn: u32,
+    scoring: ScoringMode,
+    stock_passes: StockPasses
+) -> Option<u32> {
+    match (scoring, num_turn) {
+        (ScoringMode::Vegas, 1) => Some(VEGAS_PASSES_TURN_ONE - 1),
+        (ScoringMode::Vegas, 3) => Some(VEGAS_PASSES_TURN_THREE - 1),
+        _ => stock_passes.max_recycle(),
     }
 }
 
@@ -1450,3 +2284,29 @@ fn get_atlas_index(suit: CardSuit, number: u32) -> Option<usize> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vegas_passes_follow_draw_count() {
+        assert_eq!(get_max_recycle(1, ScoringMode::Vegas, StockPasses::Unlimited), Some(0));
+        assert_eq!(get_max_recycle(3, ScoringMode::Vegas, StockPasses::Unlimited), Some(2));
+
+        // 1枚と3枚以外は、めくり直しの設定に従う
+        for num_turn in [2, 4, 5] {
+            assert_eq!(get_max_recycle(num_turn, ScoringMode::Vegas, StockPasses::One), Some(0));
+            assert_eq!(get_max_recycle(num_turn, ScoringMode::Vegas, StockPasses::Three), Some(2));
+            assert_eq!(get_max_recycle(num_turn, ScoringMode::Vegas, StockPasses::Unlimited), None);
+        }
+    }
+
+    #[test]
+    fn standard_passes_follow_option() {
+        for num_turn in 1..=5 {
+            assert_eq!(get_max_recycle(num_turn, ScoringMode::Standard, StockPasses::One), Some(0));
+            assert_eq!(get_max_recycle(num_turn, ScoringMode::Standard, StockPasses::Unlimited), None);
+        }
+    }
+}
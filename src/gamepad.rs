@@ -0,0 +1,91 @@
+use bevy::{
+    app::{App, Plugin, PreUpdate, Startup, Update},
+    input::InputSystem,
+    prelude::*,
+};
+use crate::{
+    game::{CursorAction, CursorEvent, HintEvent, NewGameEvent},
+    GameState,
+};
+
+const STICK_THRESHOLD: f32 = 0.6;
+
+/// ゲームパッドで押した方向（十字キーを押した瞬間、スティックを倒した瞬間）
+#[derive(Default, Resource)]
+pub struct GamepadDirection {
+    pub just_pressed: Option<Vec2>,
+    stick: Option<Vec2>,
+}
+
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, init_system)
+            .add_systems(PreUpdate, direction_system.after(InputSystem))
+            .add_systems(Update, button_input_system.run_if(in_state(GameState::Play)));
+    }
+}
+
+fn init_system(
+    mut commands: Commands,
+) {
+    commands.insert_resource(GamepadDirection::default());
+}
+
+/// 十字キーとスティックの方向の取得
+fn direction_system(
+    mut direction: ResMut<GamepadDirection>,
+    query: Query<&Gamepad>,
+) {
+    let dpad = query.iter().find_map(|gamepad| {
+        [
+            (GamepadButton::DPadLeft, Vec2::NEG_X),
+            (GamepadButton::DPadRight, Vec2::X),
+            (GamepadButton::DPadUp, Vec2::Y),
+            (GamepadButton::DPadDown, Vec2::NEG_Y),
+        ].into_iter().find(|(button, _)| gamepad.just_pressed(*button)).map(|(_, direction)| direction)
+    });
+
+    // スティックは倒したままでも1回だけ動かす
+    let stick = query.iter().map(|gamepad| gamepad.left_stick()).find(|stick| {
+        stick.length() > STICK_THRESHOLD
+    }).map(|stick| {
+        if stick.x.abs() > stick.y.abs() {
+            Vec2::new(stick.x.signum(), 0.)
+        } else {
+            Vec2::new(0., stick.y.signum())
+        }
+    });
+    let flicked = stick.filter(|stick| direction.stick != Some(*stick));
+    direction.stick = stick;
+    direction.just_pressed = dpad.or(flicked);
+}
+
+/// ゲームパッドのボタン入力時処理
+fn button_input_system(
+    mut commands: Commands,
+    direction: Res<GamepadDirection>,
+    query: Query<&Gamepad>,
+) {
+    if let Some(direction) = direction.just_pressed {
+        commands.trigger(CursorEvent(CursorAction::Move(direction)));
+    }
+
+    for gamepad in query.iter() {
+        if gamepad.just_pressed(GamepadButton::South) {
+            commands.trigger(CursorEvent(CursorAction::PickOrPlace));
+        } else if gamepad.just_pressed(GamepadButton::East) {
+            commands.trigger(CursorEvent(CursorAction::Cancel));
+        } else if gamepad.just_pressed(GamepadButton::West) {
+            commands.trigger(CursorEvent(CursorAction::Draw));
+        } else if gamepad.just_pressed(GamepadButton::RightTrigger) {
+            commands.trigger(CursorEvent(CursorAction::SendToFoundation));
+        } else if gamepad.just_pressed(GamepadButton::North) {
+            commands.trigger(HintEvent);
+        } else if gamepad.just_pressed(GamepadButton::Start) {
+            commands.trigger(NewGameEvent);
+        }
+    }
+}
@@ -5,9 +5,13 @@ use bevy::{
 use crate::{
     game::{
         CancelNewGameEvent,
+        ChangeGameEvent,
+        ContinueEvent,
         DealEvent,
         DifficultySelectEvent,
         GameClearEvent,
+        GameOverEvent,
+        GameSelectEvent,
         LoadEvent,
         NewGameEvent,
         PrepareEvent,
@@ -27,21 +31,39 @@ fn init_system(
     mut commands: Commands,
 ) {
     commands.add_observer(on_end_load);
+    commands.add_observer(on_select_game);
+    commands.add_observer(on_change_game);
     commands.add_observer(on_select_difficulty);
+    commands.add_observer(on_continue);
     commands.add_observer(on_end_prepare);
     commands.add_observer(on_end_deal);
     commands.add_observer(on_click_new_game);
     commands.add_observer(on_cancel_new_game);
     commands.add_observer(on_game_clear);
+    commands.add_observer(on_game_over);
 }
 
 fn on_end_load(
     _trigger: Trigger<LoadEvent>,
     mut next_state: ResMut<NextState<GameState>>,
+) {
+    next_state.set(GameState::SelectGame);
+}
+
+fn on_select_game(
+    _trigger: Trigger<GameSelectEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
     next_state.set(GameState::SelectDifficulty);
 }
 
+fn on_change_game(
+    _trigger: Trigger<ChangeGameEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    next_state.set(GameState::SelectGame);
+}
+
 fn on_select_difficulty(
     _trigger: Trigger<DifficultySelectEvent>,
     mut next_state: ResMut<NextState<GameState>>,
@@ -49,6 +71,13 @@ fn on_select_difficulty(
     next_state.set(GameState::Prepare);
 }
 
+fn on_continue(
+    _trigger: Trigger<ContinueEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    next_state.set(GameState::Play);
+}
+
 fn on_end_prepare(
     _trigger: Trigger<PrepareEvent>,
     mut next_state: ResMut<NextState<GameState>>,
@@ -83,3 +112,10 @@ fn on_game_clear(
 ) {
     next_state.set(GameState::GameClear);
 }
+
+fn on_game_over(
+    _trigger: Trigger<GameOverEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    next_state.set(GameState::GameOver);
+}
@@ -0,0 +1,347 @@
+use std::cmp::Reverse;
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    prelude::*,
+};
+use crate::{
+    board::{Board, BoardCard, Move, NUM_PILES, NUM_SUIT},
+    cardlist::CardList,
+    game::{
+        get_slot_position,
+        CardType,
+        HintEvent,
+        MoveOneStepEvent,
+        RedoEvent,
+        UndoEvent,
+    },
+    resources::{DrawRule, GameFonts, GameKind, StockRecycle},
+    GameState,
+};
+
+const HINT_COLOR: Srgba = Srgba::rgb(1., 0.9, 0.3);
+const HINT_MARKER_SIZE: Vec2 = Vec2::new(38., 52.);
+const HINT_MARKER_Z: f32 = -0.5;
+const HINT_PULSE_SPEED: f32 = 6.;
+const HINT_MESSAGE_SECONDS: f32 = 2.;
+
+#[derive(Default, Resource)]
+struct Hint {
+    moves: Vec<Move>,
+    index: usize,
+}
+
+#[derive(Component)]
+struct HintTarget;
+
+#[derive(Component)]
+struct HintMarker;
+
+#[derive(Component)]
+struct HintMessage(Timer);
+
+pub struct HintPlugin;
+
+impl Plugin for HintPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, init_system)
+            .add_systems(OnExit(GameState::Play), reset_system)
+            .add_systems(Update, (
+                key_input_system,
+                pulse_system,
+            ).run_if(in_state(GameState::Play)))
+            .add_systems(Update, message_system);
+    }
+}
+
+fn init_system(
+    mut commands: Commands,
+) {
+    commands.insert_resource(Hint::default());
+    commands.add_observer(on_hint);
+    commands.add_observer(on_move_one_step);
+    commands.add_observer(on_undo);
+    commands.add_observer(on_redo);
+}
+
+fn reset_system(
+    mut commands: Commands,
+    mut hint: ResMut<Hint>,
+    mut query_target: Query<(Entity, &mut Sprite), With<HintTarget>>,
+    query_marker: Query<Entity, With<HintMarker>>,
+) {
+    clear_hint(&mut commands, &mut hint, &mut query_target, &query_marker);
+}
+
+/// H 入力時処理
+fn key_input_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if keys.just_pressed(KeyCode::KeyH) {
+        commands.trigger(HintEvent);
+    }
+}
+
+/// ヒント対象の点滅処理
+fn pulse_system(
+    time: Res<Time>,
+    mut query_target: Query<&mut Sprite, (With<HintTarget>, Without<HintMarker>)>,
+    mut query_marker: Query<&mut Sprite, (With<HintMarker>, Without<HintTarget>)>,
+) {
+    let t = (time.elapsed_secs() * HINT_PULSE_SPEED).sin() * 0.5 + 0.5;
+    for mut sprite in query_target.iter_mut() {
+        sprite.color = Color::WHITE.mix(&Color::Srgba(HINT_COLOR), t);
+    }
+    for mut sprite in query_marker.iter_mut() {
+        sprite.color = Color::Srgba(HINT_COLOR.with_alpha(0.2 + t * 0.4));
+    }
+}
+
+/// 「動かせるカードがありません」の表示時間処理
+fn message_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut HintMessage)>,
+) {
+    for (entity, mut message) in query.iter_mut() {
+        if message.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// ヒント表示処理
+fn on_hint(
+    _trigger: Trigger<HintEvent>,
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    mut hint: ResMut<Hint>,
+    card_list: Res<CardList>,
+    draw: Res<DrawRule>,
+    game: Res<GameKind>,
+    stock_recycle: Res<StockRecycle>,
+    game_fonts: Res<GameFonts>,
+    mut query_target: Query<(Entity, &mut Sprite), With<HintTarget>>,
+    query_marker: Query<Entity, With<HintMarker>>,
+    query_message: Query<Entity, With<HintMessage>>,
+) {
+    // ヒントはクロンダイクのみ
+    if *state != GameState::Play || *game != GameKind::Klondike || card_list.0.iter().any(|card| card.dragging) {
+        return;
+    }
+
+    // 押すたびに次の候補を表示する
+    let index = if hint.moves.is_empty() { 0 } else { hint.index + 1 };
+    let mut moves = std::mem::take(&mut hint.moves);
+    clear_hint(&mut commands, &mut hint, &mut query_target, &query_marker);
+
+    let board = card_list.to_board(NUM_PILES, NUM_SUIT, draw.num_turn, &stock_recycle);
+    if moves.is_empty() {
+        moves = get_hint_moves(&board);
+    }
+
+    if moves.is_empty() {
+        for entity in query_message.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        spawn_message(&mut commands, &game_fonts);
+        return;
+    }
+
+    hint.index = index % moves.len();
+    let (src, dst, slot) = get_hint_targets(&board, moves[hint.index]);
+    for id in [src, dst].into_iter().flatten() {
+        commands.entity(card_list.0[id].entity).insert(HintTarget);
+    }
+    if dst.is_none() {
+        let position = get_slot_position(*game, slot);
+        commands.spawn((
+            Sprite {
+                custom_size: Some(HINT_MARKER_SIZE),
+                color: Color::Srgba(HINT_COLOR.with_alpha(0.2)),
+                ..Default::default()
+            },
+            Transform::from_translation(position.extend(HINT_MARKER_Z)),
+            HintMarker,
+        ));
+    }
+    hint.moves = moves;
+}
+
+fn on_move_one_step(
+    _trigger: Trigger<MoveOneStepEvent>,
+    mut commands: Commands,
+    mut hint: ResMut<Hint>,
+    mut query_target: Query<(Entity, &mut Sprite), With<HintTarget>>,
+    query_marker: Query<Entity, With<HintMarker>>,
+) {
+    clear_hint(&mut commands, &mut hint, &mut query_target, &query_marker);
+}
+
+fn on_undo(
+    _trigger: Trigger<UndoEvent>,
+    mut commands: Commands,
+    mut hint: ResMut<Hint>,
+    mut query_target: Query<(Entity, &mut Sprite), With<HintTarget>>,
+    query_marker: Query<Entity, With<HintMarker>>,
+) {
+    clear_hint(&mut commands, &mut hint, &mut query_target, &query_marker);
+}
+
+fn on_redo(
+    _trigger: Trigger<RedoEvent>,
+    mut commands: Commands,
+    mut hint: ResMut<Hint>,
+    mut query_target: Query<(Entity, &mut Sprite), With<HintTarget>>,
+    query_marker: Query<Entity, With<HintMarker>>,
+) {
+    clear_hint(&mut commands, &mut hint, &mut query_target, &query_marker);
+}
+
+/// ヒント表示の消去
+fn clear_hint(
+    commands: &mut Commands,
+    hint: &mut Hint,
+    query_target: &mut Query<(Entity, &mut Sprite), With<HintTarget>>,
+    query_marker: &Query<Entity, With<HintMarker>>,
+) {
+    hint.moves.clear();
+    hint.index = 0;
+
+    for (entity, mut sprite) in query_target.iter_mut() {
+        sprite.color = Color::WHITE;
+        commands.entity(entity).remove::<HintTarget>();
+    }
+    for entity in query_marker.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// 「動かせるカードがありません」を表示
+fn spawn_message(
+    commands: &mut Commands,
+    game_fonts: &GameFonts,
+) {
+    commands.spawn((
+        Text::new("動かせるカードがありません"),
+        TextFont {
+            font: game_fonts.dot_gothic.clone(),
+            font_size: 16.,
+            ..Default::default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(JustifyText::Center),
+        BackgroundColor(Color::srgba(0., 0., 0., 0.6)),
+        Node {
+            width: Val::Px(240.),
+            top: Val::Px(146.),
+            left: Val::Px(164.),
+            padding: UiRect::all(Val::Px(4.)),
+            ..Default::default()
+        },
+        ZIndex(1),
+        HintMessage(Timer::from_seconds(HINT_MESSAGE_SECONDS, TimerMode::Once)),
+    ));
+}
+
+/// ヒントの候補を評価の高い順に取得
+fn get_hint_moves(board: &Board) -> Vec<Move> {
+    let mut board = board.clone();
+    let mut moves: Vec<(i32, Move)> = board.legal_moves().into_iter().filter_map(|mv| {
+        evaluate_move(&mut board, mv).map(|priority| (priority, mv))
+    }).collect();
+    moves.sort_by_key(|(priority, _)| Reverse(*priority));
+    moves.into_iter().map(|(_, mv)| mv).collect()
+}
+
+/// 移動の評価値を取得（役に立たない移動は None）
+fn evaluate_move(board: &mut Board, mv: Move) -> Option<i32> {
+    match mv {
+        Move::WasteToFoundation(_) => Some(100),
+        Move::PileToFoundation { src, .. } => {
+            // 裏向きのカードをめくれるなら優先する
+            let pile = &board.piles[src];
+            if pile.len() >= 2 && pile[pile.len() - 2].facedown {
+                Some(120)
+            } else {
+                Some(100)
+            }
+        }
+        Move::PileToPile { src, index, dst } => {
+            if index == 0 {
+                // キングを空いた列から空いた列へ移しても意味がない
+                if board.piles[dst].is_empty() {
+                    return None;
+                }
+                return Some(50);
+            }
+            if board.piles[src][index - 1].facedown {
+                let num_facedown = board.piles[src].iter().filter(|card| card.facedown).count();
+                return Some(80 + num_facedown as i32);
+            }
+
+            // 列を分けた結果、下のカードを組札に移せる場合のみ
+            let applied = board.apply(mv)?;
+            let useful = board.pile_to_foundation(src).is_some();
+            board.revert(&applied);
+            useful.then_some(70)
+        }
+        Move::WasteToPile(_) => Some(60),
+        Move::FoundationToPile { dst, .. } => {
+            // 戻したカードに山札か場札を重ねられる場合のみ
+            let applied = board.apply(mv)?;
+            let useful = board.waste.last().is_some_and(|_| board.can_apply(Move::WasteToPile(dst)))
+                || (0..board.piles.len()).any(|src| {
+                    board.piles[src].iter().position(|card| !card.facedown).is_some_and(|index| {
+                        index > 0 && board.can_apply(Move::PileToPile { src, index, dst })
+                    })
+                });
+            board.revert(&applied);
+            useful.then_some(20)
+        }
+        Move::StockToWaste | Move::WasteToStock => {
+            // 山札の中に使えるカードがある場合のみ
+            let useful = board.stock.iter().chain(board.waste.iter()).any(|card| {
+                board.can_stack_anywhere(card)
+            });
+            match (useful, mv) {
+                (true, Move::StockToWaste) => Some(1),
+                (true, _) => Some(0),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// 移動元のカード、移動先のカード、移動先の置き場を取得
+fn get_hint_targets(board: &Board, mv: Move) -> (Option<usize>, Option<usize>, CardType) {
+    match mv {
+        Move::StockToWaste => (top(&board.stock), None, CardType::Stock),
+        Move::WasteToStock => (top(&board.waste), None, CardType::Stock),
+        Move::WasteToPile(dst) => {
+            (top(&board.waste), top(&board.piles[dst]), CardType::Pile(dst as u32))
+        }
+        Move::WasteToFoundation(slot) => get_foundation_targets(board, board.waste.last(), slot),
+        Move::PileToPile { src, index, dst } => {
+            (board.piles[src].get(index).map(|card| card.id), top(&board.piles[dst]), CardType::Pile(dst as u32))
+        }
+        Move::PileToFoundation { src, slot } => get_foundation_targets(board, board.piles[src].last(), slot),
+        Move::FoundationToPile { slot, dst } => {
+            (top(&board.foundations[slot]), top(&board.piles[dst]), CardType::Pile(dst as u32))
+        }
+    }
+}
+
+/// 組札へ移動するときの対象を取得
+fn get_foundation_targets(board: &Board, card: Option<&BoardCard>, slot: usize) -> (Option<usize>, Option<usize>, CardType) {
+    match card {
+        Some(card) => (Some(card.id), top(&board.foundations[slot]), CardType::Foundation(slot as u32)),
+        None => (None, None, CardType::Waste),
+    }
+}
+
+/// カード列の一番上のカードを取得
+fn top(cards: &[BoardCard]) -> Option<usize> {
+    cards.last().map(|card| card.id)
+}
@@ -0,0 +1,164 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    prelude::*,
+};
+use crate::{
+    board::AppliedMove,
+    cardlist::CardList,
+    game::{CardInfo, CardType, ContinueEvent, MoveStep, RedoEvent, UndoEvent},
+    resources::StockRecycle,
+    GameState,
+};
+
+#[derive(Clone, Copy)]
+pub struct CardLocation {
+    pub card_type: CardType,
+    pub order: i32,
+    pub facedown: bool,
+    pub clickable: bool,
+}
+
+impl CardLocation {
+    pub fn new(card: &CardInfo) -> Self {
+        Self {
+            card_type: card.card_type,
+            order: card.order,
+            facedown: card.facedown,
+            clickable: card.clickable,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Snapshot {
+    pub cards: Vec<CardLocation>,
+    pub score: i32,
+    pub movement: u32,
+    pub num_recycle: u32,
+}
+
+impl Snapshot {
+    pub fn new(card_list: &CardList, score: i32, movement: u32, stock_recycle: &StockRecycle) -> Self {
+        let cards = card_list.0.iter().map(CardLocation::new).collect();
+
+        Self {
+            cards,
+            score,
+            movement,
+            num_recycle: stock_recycle.count,
+        }
+    }
+}
+
+pub struct HistoryEntry {
+    pub steps: Vec<MoveStep>,
+    pub before: Snapshot,
+    pub after: Option<Snapshot>,
+    pub applied: Option<AppliedMove>,
+}
+
+#[derive(Default, Resource)]
+pub struct History {
+    undo: Vec<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+}
+
+impl History {
+    /// 移動を記録
+    pub fn push(&mut self, step: MoveStep, before: Snapshot) {
+        self.undo.push(HistoryEntry {
+            steps: vec![step],
+            before,
+            after: None,
+            applied: None,
+        });
+        self.redo.clear();
+    }
+
+    /// 直前の移動で盤面に適用した移動を記録
+    pub fn push_applied(&mut self, applied: AppliedMove) {
+        if let Some(entry) = self.undo.last_mut() {
+            entry.applied = Some(applied);
+        }
+    }
+
+    /// 直前の移動に付随する移動を記録
+    pub fn push_step(&mut self, step: MoveStep) {
+        if let Some(entry) = self.undo.last_mut() {
+            entry.steps.push(step);
+        }
+    }
+
+    /// 直前の移動を取り消す
+    pub fn undo(&mut self, current: Snapshot) -> Option<&HistoryEntry> {
+        let mut entry = self.undo.pop()?;
+        entry.after = Some(current);
+        self.redo.push(entry);
+        self.redo.last()
+    }
+
+    /// 取り消した移動をやり直す
+    pub fn redo(&mut self) -> Option<&HistoryEntry> {
+        let entry = self.redo.pop()?;
+        self.undo.push(entry);
+        self.undo.last()
+    }
+
+    /// 履歴を消去
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, init_system)
+            .add_systems(OnEnter(GameState::Prepare), reset_system)
+            .add_systems(Update, key_input_system.run_if(in_state(GameState::Play)));
+    }
+}
+
+fn init_system(
+    mut commands: Commands,
+) {
+    commands.insert_resource(History::default());
+    commands.add_observer(on_continue);
+}
+
+fn reset_system(
+    mut history: ResMut<History>,
+) {
+    history.clear();
+}
+
+fn on_continue(
+    _trigger: Trigger<ContinueEvent>,
+    mut history: ResMut<History>,
+) {
+    history.clear();
+}
+
+/// Ctrl+Z / Ctrl+Y 入力時処理
+fn key_input_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if keys.just_pressed(KeyCode::KeyZ) {
+        if shift {
+            commands.trigger(RedoEvent);
+        } else {
+            commands.trigger(UndoEvent);
+        }
+    } else if keys.just_pressed(KeyCode::KeyY) {
+        commands.trigger(RedoEvent);
+    }
+}
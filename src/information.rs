@@ -1,5 +1,12 @@
 use bevy::{app::{App, Plugin, Update}, prelude::*};
-use crate::{game::{AddScoreEvent, MoveOneStepEvent}, resources::GameFonts, GameState};
+use serde::{Deserialize, Serialize};
+use crate::{
+    game::{AddScoreEvent, ContinueEvent, MoveOneStepEvent, RestoreInformationEvent},
+    resources::{DealSeed, GameFonts, ScoringMode},
+    savedata::{self, SavedGame},
+    solver::{AnalysisStatus, DealAnalysis, SolveResult},
+    GameState,
+};
 
 #[derive(Component)]
 struct TimeText;
@@ -10,6 +17,12 @@ struct ScoreText;
 #[derive(Component)]
 struct MovementText;
 
+#[derive(Component)]
+struct SeedText;
+
+#[derive(Component)]
+struct AnalysisText;
+
 #[derive(Component)]
 struct UpdateScore(i32);
 
@@ -17,13 +30,16 @@ struct UpdateScore(i32);
 struct UpdateMovement(u32);
 
 #[derive(Resource)]
-struct Time(f32);
+pub struct Time(pub f32);
 
 #[derive(Resource)]
-struct Score(i32);
+pub struct Score(pub i32);
 
 #[derive(Resource)]
-struct Movement(u32);
+pub struct Movement(pub u32);
+
+#[derive(Default, Deserialize, Resource, Serialize)]
+pub struct Bank(pub i32);
 
 pub struct InformationPlugin;
 
@@ -35,7 +51,9 @@ impl Plugin for InformationPlugin {
             .add_systems(OnEnter(GameState::Prepare), reset_system)
             .add_systems(Update, update_time_system.run_if(in_state(GameState::Play)))
             .add_systems(Update, update_score_system)
-            .add_systems(Update, update_movement_system);
+            .add_systems(Update, update_movement_system)
+            .add_systems(Update, update_seed_system.run_if(resource_exists_and_changed::<DealSeed>))
+            .add_systems(Update, update_analysis_system.run_if(resource_exists_and_changed::<DealAnalysis>));
     }
 }
 
@@ -45,8 +63,11 @@ fn init_system(
     commands.insert_resource(Time(0.));
     commands.insert_resource(Score(0));
     commands.insert_resource(Movement(0));
+    commands.insert_resource(savedata::load_bank());
     commands.add_observer(on_score);
     commands.add_observer(on_move_one_step);
+    commands.add_observer(on_restore);
+    commands.add_observer(on_continue);
 }
 
 fn reset_system(
@@ -121,6 +142,42 @@ fn spawn_ui(
         ZIndex(0),
         MovementText,
     ));
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font: game_fonts.dot_gothic.clone(),
+            font_size: 12.,
+            ..Default::default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Node {
+            width: Val::Px(200.),
+            top: Val::Px(38.),
+            left: Val::Px(184.),
+            ..Default::default()
+        },
+        ZIndex(0),
+        SeedText,
+    ));
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font: game_fonts.dot_gothic.clone(),
+            font_size: 12.,
+            ..Default::default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Node {
+            width: Val::Px(200.),
+            top: Val::Px(52.),
+            left: Val::Px(184.),
+            ..Default::default()
+        },
+        ZIndex(0),
+        AnalysisText,
+    ));
 }
 
 fn update_time_system(
@@ -134,24 +191,40 @@ fn update_time_system(
         let hour = time / 60 / 60;
         let minute = time / 60 % 60;
         let second = time % 60;
-        text.0 = String::from(format!("{:01}:{:02}:{:02}", hour, minute, second));
+        text.0 = format!("{:01}:{:02}:{:02}", hour, minute, second);
     }
 }
 
 fn update_score_system(
     mut commands: Commands,
     mut res_score: ResMut<Score>,
+    mut bank: ResMut<Bank>,
+    scoring: Option<Res<ScoringMode>>,
     mut query_text: Query<&mut Text, With<ScoreText>>,
     query_update_score: Query<(Entity, &UpdateScore)>,
 ) {
+    let scoring = scoring.map_or(ScoringMode::Standard, |scoring| *scoring);
     for (entity, update_score) in query_update_score.iter() {
         if let Ok(mut text) = query_text.get_single_mut() {
             let mut score = res_score.0 + update_score.0;
-            if score < 0 {
-                score = 0;
+            match scoring {
+                ScoringMode::Standard => {
+                    if score < 0 {
+                        score = 0;
+                    }
+                    res_score.0 = score;
+                    text.0 = format!("スコア  {}", res_score.0);
+                }
+                ScoringMode::Vegas => {
+                    // ベガス方式では得点の増減をそのまま所持金に反映する
+                    if update_score.0 != 0 {
+                        bank.0 += update_score.0;
+                        savedata::save_bank(&bank);
+                    }
+                    res_score.0 = score;
+                    text.0 = format!("所持金  {}", format_dollar(bank.0));
+                }
             }
-            res_score.0 = score;
-            text.0 = String::from(format!("スコア  {}", res_score.0));
         }
         commands.entity(entity).despawn();
     }
@@ -166,12 +239,37 @@ fn update_movement_system(
     for (entity, update_movement) in query_update_movement.iter() {
         if let Ok(mut text) = query_text.get_single_mut() {
             res_movement.0 += update_movement.0;
-            text.0 = String::from(format!("移動回数  {}", res_movement.0));
+            text.0 = format!("移動回数  {}", res_movement.0);
         }
         commands.entity(entity).despawn();
     }
 }
 
+fn update_seed_system(
+    deal_seed: Res<DealSeed>,
+    mut query: Query<&mut Text, With<SeedText>>,
+) {
+    if let Ok(mut text) = query.get_single_mut() {
+        text.0 = format!("配布番号  #{}", deal_seed.seed);
+    }
+}
+
+fn update_analysis_system(
+    analysis: Res<DealAnalysis>,
+    mut query: Query<&mut Text, With<AnalysisText>>,
+) {
+    if let Ok(mut text) = query.get_single_mut() {
+        text.0 = match analysis.status {
+            AnalysisStatus::None => String::new(),
+            AnalysisStatus::Searching => String::from("勝てる配布を探しています…"),
+            AnalysisStatus::Solving => String::from("判定中…"),
+            AnalysisStatus::Done(SolveResult::Winnable(num)) => format!("勝てる配布（{}手の解あり）", num),
+            AnalysisStatus::Done(SolveResult::Unwinnable) => String::from("勝てない配布"),
+            AnalysisStatus::Done(SolveResult::Unknown) => String::from("判定できませんでした"),
+        };
+    }
+}
+
 fn on_score(
     trigger: Trigger<AddScoreEvent>,
     mut commands: Commands,
@@ -185,3 +283,44 @@ fn on_move_one_step(
 ) {
     commands.spawn(UpdateMovement(1));
 }
+
+fn on_restore(
+    trigger: Trigger<RestoreInformationEvent>,
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    mut movement: ResMut<Movement>,
+    mut bank: ResMut<Bank>,
+    scoring: Res<ScoringMode>,
+) {
+    // 元に戻した分の得点を所持金にも反映する
+    if *scoring == ScoringMode::Vegas && score.0 != trigger.event().score {
+        bank.0 += trigger.event().score - score.0;
+        savedata::save_bank(&bank);
+    }
+
+    score.0 = trigger.event().score;
+    movement.0 = trigger.event().movement;
+
+    commands.spawn(UpdateScore(0));
+    commands.spawn(UpdateMovement(0));
+}
+
+fn on_continue(
+    _trigger: Trigger<ContinueEvent>,
+    mut time: ResMut<Time>,
+    mut score: ResMut<Score>,
+    saved_game: Res<SavedGame>,
+) {
+    // 保存した得点はすでに所持金に含まれている
+    time.0 = saved_game.time;
+    score.0 = saved_game.score;
+}
+
+/// ドル表記の取得
+fn format_dollar(value: i32) -> String {
+    if value < 0 {
+        format!("-${}", -value)
+    } else {
+        format!("${}", value)
+    }
+}
@@ -0,0 +1,391 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    prelude::*,
+};
+use crate::{
+    cardlist::CardList,
+    game::{
+        get_slot_position,
+        Card,
+        CardInfo,
+        CardType,
+        ContinueEvent,
+        CursorAction,
+        CursorEvent,
+        DealEvent,
+        RecycleStockEvent,
+        SelectCardEvent,
+        StackCardEvent,
+    },
+    resources::GameKind,
+    GameState,
+};
+
+const CARD_SIZE: Vec2 = Vec2::new(38., 52.);
+const OUTLINE_WIDTH: f32 = 2.;
+const OUTLINE_MARGIN: f32 = 2.;
+const OUTLINE_Z: f32 = 200.;
+const FOCUS_COLOR: Color = Color::srgb(0.3, 0.8, 1.);
+const HELD_COLOR: Color = Color::srgb(1., 0.9, 0.3);
+
+/// キーボードで選んでいる置き場と持ち上げたカード
+#[derive(Resource)]
+struct Cursor {
+    active: bool,
+    slot: CardType,
+    depth: usize,
+    position: Vec2,
+    held: Option<(Entity, CardType)>,
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Self {
+            active: false,
+            slot: CardType::Stock,
+            depth: 0,
+            position: Vec2::ZERO,
+            held: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum OutlineKind {
+    Focus,
+    Held,
+}
+
+/// 枠線の1辺（0: 上、1: 下、2: 左、3: 右）
+#[derive(Component)]
+struct OutlineEdge {
+    kind: OutlineKind,
+    side: usize,
+}
+
+pub struct KeyboardPlugin;
+
+impl Plugin for KeyboardPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, init_system)
+            .add_systems(OnExit(GameState::Play), reset_system)
+            .add_systems(Update, (
+                key_input_system,
+                cursor_system,
+                outline_system,
+            ).chain().run_if(in_state(GameState::Play)));
+    }
+}
+
+fn init_system(
+    mut commands: Commands,
+) {
+    commands.insert_resource(Cursor::default());
+    commands.add_observer(on_cursor);
+    commands.add_observer(on_deal);
+    commands.add_observer(on_continue);
+
+    for kind in [OutlineKind::Focus, OutlineKind::Held] {
+        let color = match kind {
+            OutlineKind::Focus => FOCUS_COLOR,
+            OutlineKind::Held => HELD_COLOR,
+        };
+        for side in 0..4 {
+            commands.spawn((
+                Sprite::from_color(color, Vec2::ONE),
+                Transform::default(),
+                Visibility::Hidden,
+                OutlineEdge {
+                    kind,
+                    side,
+                },
+            ));
+        }
+    }
+}
+
+fn reset_system(
+    mut cursor: ResMut<Cursor>,
+    mut query: Query<&mut Visibility, With<OutlineEdge>>,
+) {
+    cursor.held = None;
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn on_deal(
+    _trigger: Trigger<DealEvent>,
+    mut cursor: ResMut<Cursor>,
+) {
+    *cursor = Cursor::default();
+}
+
+fn on_continue(
+    _trigger: Trigger<ContinueEvent>,
+    mut cursor: ResMut<Cursor>,
+) {
+    *cursor = Cursor::default();
+}
+
+/// キー入力時処理
+fn key_input_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    // Ctrl と一緒に押したときは取り消し・やり直しに使う
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    let action = if keys.just_pressed(KeyCode::ArrowLeft) {
+        CursorAction::Move(Vec2::NEG_X)
+    } else if keys.just_pressed(KeyCode::ArrowRight) {
+        CursorAction::Move(Vec2::X)
+    } else if keys.just_pressed(KeyCode::ArrowUp) {
+        CursorAction::Move(Vec2::Y)
+    } else if keys.just_pressed(KeyCode::ArrowDown) {
+        CursorAction::Move(Vec2::NEG_Y)
+    } else if keys.just_pressed(KeyCode::Space) {
+        CursorAction::PickOrPlace
+    } else if keys.just_pressed(KeyCode::Enter) {
+        CursorAction::Select
+    } else if keys.just_pressed(KeyCode::KeyD) {
+        CursorAction::Draw
+    } else if keys.just_pressed(KeyCode::KeyF) {
+        CursorAction::SendToFoundation
+    } else if keys.just_pressed(KeyCode::Escape) {
+        CursorAction::Cancel
+    } else {
+        return;
+    };
+    commands.trigger(CursorEvent(action));
+}
+
+/// カーソルの置き場と持ち上げたカードの確認
+fn cursor_system(
+    mut cursor: ResMut<Cursor>,
+    card_list: Res<CardList>,
+    game: Res<GameKind>,
+) {
+    // 置き場がなくなったとき（ピラミッドのカードを取り除いたときなど）は近い置き場に移る
+    let slots = game.ruleset().cursor_slots(&card_list);
+    if !slots.contains(&cursor.slot) {
+        let position = cursor.position;
+        if let Some(slot) = slots.iter().min_by(|a, b| {
+            let a = get_target_position(&card_list, *game, **a).distance(position);
+            let b = get_target_position(&card_list, *game, **b).distance(position);
+            a.total_cmp(&b)
+        }) {
+            cursor.slot = *slot;
+        }
+        cursor.depth = 0;
+    }
+    cursor.depth = cursor.depth.min(get_max_depth(&card_list, cursor.slot));
+    cursor.position = get_from_position(&card_list, *game, cursor.slot, cursor.depth);
+
+    // 持ち上げたカードがほかの操作で動いたときは持ち上げをやめる
+    if let Some((entity, card_type)) = cursor.held {
+        if card_list.get(entity).is_none_or(|card| card.card_type != card_type) {
+            cursor.held = None;
+        }
+    }
+}
+
+/// カーソル操作時処理（キーボード、ゲームパッド）
+fn on_cursor(
+    trigger: Trigger<CursorEvent>,
+    mut commands: Commands,
+    mut cursor: ResMut<Cursor>,
+    card_list: Res<CardList>,
+    game: Res<GameKind>,
+) {
+    cursor.active = true;
+    match trigger.event().0 {
+        CursorAction::Move(direction) => {
+            let slots = game.ruleset().cursor_slots(&card_list);
+            move_cursor(&mut cursor, &card_list, *game, &slots, direction);
+        }
+        CursorAction::PickOrPlace => {
+            if let Some((entity, card_type)) = cursor.held.take() {
+                // 持ち上げた置き場でもう一度押したときは元に戻す
+                if card_type != cursor.slot {
+                    commands.trigger_targets(StackCardEvent(vec![cursor.slot]), entity);
+                }
+            } else if cursor.slot == CardType::Stock {
+                draw_stock(&mut commands, &card_list, *game);
+            } else if let Some(card) = get_focused_card(&card_list, cursor.slot, cursor.depth) {
+                if card.clickable && game.ruleset().can_drag(&card_list, card) {
+                    cursor.held = Some((card.entity, card.card_type));
+                }
+            }
+        }
+        CursorAction::Select => {
+            // クリックしたときと同じく、置ける場所に自動で移動する
+            cursor.held = None;
+            if cursor.slot == CardType::Stock {
+                draw_stock(&mut commands, &card_list, *game);
+            } else if let Some(card) = get_focused_card(&card_list, cursor.slot, cursor.depth) {
+                commands.trigger_targets(SelectCardEvent, card.entity);
+            }
+        }
+        CursorAction::Draw => draw_stock(&mut commands, &card_list, *game),
+        CursorAction::SendToFoundation => {
+            // 持ち上げたカード、なければ選んでいるカードを組札に送る
+            let target = cursor.held.take().map(|(entity, _)| entity).or_else(|| {
+                get_focused_card(&card_list, cursor.slot, cursor.depth).map(|card| card.entity)
+            });
+            if let Some(entity) = target {
+                let dsts = (0..game.ruleset().num_foundations() as u32).map(CardType::Foundation).collect();
+                commands.trigger_targets(StackCardEvent(dsts), entity);
+            }
+        }
+        CursorAction::Cancel => cursor.held = None,
+    }
+}
+
+/// 枠線の表示処理
+fn outline_system(
+    cursor: Res<Cursor>,
+    card_list: Res<CardList>,
+    game: Res<GameKind>,
+    query_card: Query<&Transform, With<Card>>,
+    mut query_edge: Query<(&OutlineEdge, &mut Sprite, &mut Transform, &mut Visibility), Without<Card>>,
+) {
+    let focus = cursor.active.then(|| {
+        match get_focused_card(&card_list, cursor.slot, cursor.depth) {
+            Some(card) => get_cards_rect(&card_list, card, &query_card),
+            None => Rect::from_center_size(get_slot_position(*game, cursor.slot), CARD_SIZE),
+        }
+    });
+    let held = cursor.held.and_then(|(entity, _)| card_list.get(entity)).map(|card| {
+        get_cards_rect(&card_list, card, &query_card)
+    });
+
+    for (edge, mut sprite, mut transform, mut visibility) in query_edge.iter_mut() {
+        let rect = match edge.kind {
+            OutlineKind::Focus => focus,
+            OutlineKind::Held => held,
+        };
+        let Some(rect) = rect else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        let half = rect.half_size() + OUTLINE_MARGIN;
+        let (offset, size) = match edge.side {
+            0 => (Vec2::new(0., half.y), Vec2::new(half.x * 2. + OUTLINE_WIDTH, OUTLINE_WIDTH)),
+            1 => (Vec2::new(0., -half.y), Vec2::new(half.x * 2. + OUTLINE_WIDTH, OUTLINE_WIDTH)),
+            2 => (Vec2::new(-half.x, 0.), Vec2::new(OUTLINE_WIDTH, half.y * 2. + OUTLINE_WIDTH)),
+            _ => (Vec2::new(half.x, 0.), Vec2::new(OUTLINE_WIDTH, half.y * 2. + OUTLINE_WIDTH)),
+        };
+        // 持ち上げたカードの枠線は選んでいる置き場の枠線より手前に表示
+        let z = match edge.kind {
+            OutlineKind::Focus => OUTLINE_Z,
+            OutlineKind::Held => OUTLINE_Z + 1.,
+        };
+        transform.translation = (rect.center() + offset).extend(z);
+        sprite.custom_size = Some(size);
+        *visibility = Visibility::Visible;
+    }
+}
+
+/// カーソルを移動（場札の列の中では持ち上げるカードの深さを変える）
+fn move_cursor(
+    cursor: &mut Cursor,
+    card_list: &CardList,
+    game: GameKind,
+    slots: &[CardType],
+    direction: Vec2,
+) {
+    if let CardType::Pile(_) = cursor.slot {
+        if direction == Vec2::Y && cursor.depth < get_max_depth(card_list, cursor.slot) {
+            cursor.depth += 1;
+            return;
+        }
+        if direction == Vec2::NEG_Y && cursor.depth > 0 {
+            cursor.depth -= 1;
+            return;
+        }
+    }
+
+    // 押した向きにある置き場のうち、向きからのずれが少なく近いところを選ぶ
+    let from = get_from_position(card_list, game, cursor.slot, cursor.depth);
+    let next = slots.iter().filter(|slot| **slot != cursor.slot).filter_map(|slot| {
+        let offset = get_target_position(card_list, game, *slot) - from;
+        let along = offset.dot(direction);
+        (along > 1.).then_some((*slot, along + offset.perp_dot(direction).abs() * 2.))
+    }).min_by(|a, b| a.1.total_cmp(&b.1));
+
+    if let Some((slot, _)) = next {
+        cursor.slot = slot;
+        cursor.depth = 0;
+    }
+}
+
+/// 山札をめくる（山札がないときは山札に戻す）
+fn draw_stock(
+    commands: &mut Commands,
+    card_list: &CardList,
+    game: GameKind,
+) {
+    if !game.ruleset().has_stock() {
+        return;
+    }
+    match get_slot_cards(card_list, CardType::Stock).last() {
+        Some(card) => commands.trigger_targets(SelectCardEvent, card.entity),
+        None => commands.trigger(RecycleStockEvent),
+    }
+}
+
+/// 置き場のカードを下から順に取得
+fn get_slot_cards(card_list: &CardList, slot: CardType) -> Vec<&CardInfo> {
+    let mut cards: Vec<&CardInfo> = card_list.0.iter().filter(|card| card.card_type == slot).collect();
+    cards.sort_by_key(|card| card.order);
+    cards
+}
+
+/// 選んでいるカードを取得（depth は一番上から数えた枚数）
+fn get_focused_card(card_list: &CardList, slot: CardType, depth: usize) -> Option<&CardInfo> {
+    let cards = get_slot_cards(card_list, slot);
+    cards.len().checked_sub(depth + 1).map(|index| cards[index])
+}
+
+/// 選べる深さの上限を取得（場札の列の表向きのカードのみ）
+fn get_max_depth(card_list: &CardList, slot: CardType) -> usize {
+    if !matches!(slot, CardType::Pile(_)) {
+        return 0;
+    }
+    let cards = get_slot_cards(card_list, slot);
+    cards.iter().rev().take_while(|card| !card.facedown).count().saturating_sub(1)
+}
+
+/// カーソルの移動元の位置を取得
+fn get_from_position(card_list: &CardList, game: GameKind, slot: CardType, depth: usize) -> Vec2 {
+    get_focused_card(card_list, slot, depth).map_or_else(|| {
+        get_slot_position(game, slot)
+    }, |card| card.dst_position.truncate())
+}
+
+/// カーソルの移動先の位置を取得（カードがあるときは一番上のカード）
+fn get_target_position(card_list: &CardList, game: GameKind, slot: CardType) -> Vec2 {
+    get_from_position(card_list, game, slot, 0)
+}
+
+/// カードとその上に重なったカードを囲む範囲を取得
+fn get_cards_rect(
+    card_list: &CardList,
+    card: &CardInfo,
+    query_card: &Query<&Transform, With<Card>>,
+) -> Rect {
+    get_slot_cards(card_list, card.card_type).into_iter().filter(|other| {
+        other.order >= card.order
+    }).filter_map(|other| {
+        query_card.get(other.entity).ok()
+    }).map(|transform| {
+        Rect::from_center_size(transform.translation.truncate(), CARD_SIZE)
+    }).reduce(|a, b| a.union(b)).unwrap_or_else(|| {
+        Rect::from_center_size(card.dst_position.truncate(), CARD_SIZE)
+    })
+}
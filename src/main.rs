@@ -8,20 +8,46 @@ use bevy::{
 };
 use bevy_embedded_assets::EmbeddedAssetPlugin;
 use game::{GamePlugin, GameState};
+use gamepad::GamepadPlugin;
 use gamestate::GameStatePlugin;
+use hint::HintPlugin;
+use history::HistoryPlugin;
 use information::InformationPlugin;
+use keyboard::KeyboardPlugin;
 use loading::LoadingPlugin;
+use menuinput::MenuInputPlugin;
+use savedata::SaveDataPlugin;
 use selectdifficulty::SelectDifficultyPlugin;
+use selectgame::SelectGamePlugin;
+use solver::SolverPlugin;
+use stats::StatsPlugin;
 
 mod audio;
+mod board;
+mod canfield;
 mod cardlist;
 mod components;
+mod freecell;
 mod game;
+mod gamepad;
 mod gamestate;
+mod hint;
+mod history;
 mod information;
+mod keyboard;
 mod loading;
+mod menuinput;
+mod peaks;
+mod pyramid;
 mod resources;
+mod ruleset;
+mod savedata;
 mod selectdifficulty;
+mod selectgame;
+mod solver;
+mod spider;
+mod stats;
+mod yukon;
 
 fn main() {
     App::new()
@@ -37,10 +63,19 @@ fn main() {
         .add_plugins(AudioPlugin)
         .add_plugins(EmbeddedAssetPlugin::default())
         .add_plugins(GamePlugin)
+        .add_plugins(GamepadPlugin)
         .add_plugins(GameStatePlugin)
+        .add_plugins(HintPlugin)
+        .add_plugins(HistoryPlugin)
         .add_plugins(InformationPlugin)
+        .add_plugins(KeyboardPlugin)
         .add_plugins(LoadingPlugin)
+        .add_plugins(MenuInputPlugin)
+        .add_plugins(SaveDataPlugin)
         .add_plugins(SelectDifficultyPlugin)
+        .add_plugins(SelectGamePlugin)
+        .add_plugins(SolverPlugin)
+        .add_plugins(StatsPlugin)
         .insert_state(GameState::Loading)
         .run();
 }
@@ -0,0 +1,136 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    prelude::*,
+};
+use crate::{
+    game::{CancelNewGameEvent, PressButtonEvent},
+    gamepad::GamepadDirection,
+    stats::UIStats,
+    GameState,
+};
+
+const FOCUS_COLOR: Color = Color::srgb(0.3, 0.8, 1.);
+
+/// キーボード、ゲームパッドで操作する画面
+#[derive(Component)]
+pub struct Menu;
+
+/// キーボード、ゲームパッドで選んでいるボタン
+#[derive(Component)]
+pub struct MenuFocus;
+
+pub struct MenuInputPlugin;
+
+impl Plugin for MenuInputPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, init_system)
+            .add_systems(
+                Update,
+                (
+                    activate_input_system,
+                    focus_input_system,
+                    cancel_input_system,
+                ).chain().run_if(
+                    in_state(GameState::SelectGame)
+                    .or(in_state(GameState::SelectDifficulty))
+                    .or(in_state(GameState::NewGame))
+                    .or(in_state(GameState::GameClear))
+                    .or(in_state(GameState::GameOver))
+                ).run_if(not(any_with_component::<UIStats>))
+            );
+    }
+}
+
+fn init_system(
+    mut commands: Commands,
+) {
+    commands.add_observer(on_click_button);
+}
+
+/// ボタンのクリック時処理（ボタンを押したことにする）
+fn on_click_button(
+    click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    query: Query<(), With<Button>>,
+) {
+    if click.button == PointerButton::Primary && query.contains(click.entity()) {
+        commands.trigger_targets(PressButtonEvent, click.entity());
+    }
+}
+
+/// キーボード、ゲームパッドでのボタンの選択（最初の入力では選んでいるボタンを表示するのみ）
+fn focus_input_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    direction: Res<GamepadDirection>,
+    query_root: Query<Entity, With<Menu>>,
+    query_children: Query<&Children>,
+    query_button: Query<(Entity, &GlobalTransform), With<Button>>,
+    query_focus: Query<(Entity, &GlobalTransform, Has<Outline>), With<MenuFocus>>,
+) {
+    let direction = [
+        (KeyCode::ArrowLeft, Vec2::NEG_X),
+        (KeyCode::ArrowRight, Vec2::X),
+        (KeyCode::ArrowUp, Vec2::Y),
+        (KeyCode::ArrowDown, Vec2::NEG_Y),
+    ].into_iter().find(|(key, _)| keys.just_pressed(*key)).map(|(_, direction)| direction).or(direction.just_pressed);
+    let (Some(direction), Ok(root), Ok((current, transform, shown))) =
+        (direction, query_root.get_single(), query_focus.get_single()) else {
+        return;
+    };
+    if !shown {
+        commands.entity(current).insert(Outline::new(Val::Px(1.), Val::Px(2.), FOCUS_COLOR));
+        return;
+    }
+
+    // 押した向きにあるボタンのうち、向きからのずれが少なく近いものを選ぶ（UI は下向きが正）
+    let direction = Vec2::new(direction.x, -direction.y);
+    let from = transform.translation().truncate();
+    let next = query_children.iter_descendants(root).filter(|entity| *entity != current).filter_map(|entity| {
+        let (entity, transform) = query_button.get(entity).ok()?;
+        let offset = transform.translation().truncate() - from;
+        let along = offset.dot(direction);
+        (along > 1.).then_some((entity, along + offset.perp_dot(direction).abs() * 2.))
+    }).min_by(|a, b| a.1.total_cmp(&b.1));
+
+    if let Some((next, _)) = next {
+        commands.entity(current).remove::<(MenuFocus, Outline)>();
+        commands.entity(next).insert((
+            MenuFocus,
+            Outline::new(Val::Px(1.), Val::Px(2.), FOCUS_COLOR),
+        ));
+    }
+}
+
+/// キーボード、ゲームパッドで選んでいるボタンを押す
+fn activate_input_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    query_gamepad: Query<&Gamepad>,
+    query_focus: Query<Entity, With<MenuFocus>>,
+) {
+    let activate = keys.any_just_pressed([KeyCode::Enter, KeyCode::Space])
+        || query_gamepad.iter().any(|gamepad| gamepad.just_pressed(GamepadButton::South));
+    if !activate {
+        return;
+    }
+
+    if let Ok(entity) = query_focus.get_single() {
+        commands.trigger_targets(PressButtonEvent, entity);
+    }
+}
+
+/// キーボード、ゲームパッドで新しいゲームの選択をやめる
+fn cancel_input_system(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    query_gamepad: Query<&Gamepad>,
+) {
+    let cancel = keys.just_pressed(KeyCode::Escape)
+        || query_gamepad.iter().any(|gamepad| gamepad.just_pressed(GamepadButton::East));
+    if cancel && *state == GameState::NewGame {
+        commands.trigger(CancelNewGameEvent);
+    }
+}
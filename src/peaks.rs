@@ -0,0 +1,99 @@
+pub const NUM_PEAKS_CARDS: usize = 28;
+pub const NUM_GOLF_PILES: usize = 7;
+pub const NUM_GOLF_PILE_CARDS: usize = 5;
+pub const NUM_PEAKS_TURN: u32 = 1;
+const ROW_STARTS: [usize; 5] = [0, 3, 9, 18, NUM_PEAKS_CARDS];
+
+/// 段と段の中の位置を取得（トライピークス）
+pub fn row_col(index: usize) -> (usize, usize) {
+    let row = ROW_STARTS.iter().rposition(|start| *start <= index).unwrap_or_default();
+    (row, index - ROW_STARTS[row])
+}
+
+/// 横方向の位置をカードの間隔を単位にして取得（トライピークス）
+pub fn column_x(index: usize) -> f32 {
+    let (row, col) = row_col(index);
+    match row {
+        0 => col as f32 * 3. - 3.,
+        1 => (col / 2 * 3 + col % 2) as f32 - 3.5,
+        2 => col as f32 - 4.,
+        _ => col as f32 - 4.5,
+    }
+}
+
+/// 上に重なるカードの位置を取得（トライピークス、最下段には重なるカードがない）
+pub fn covering(index: usize) -> Option<[usize; 2]> {
+    let (row, col) = row_col(index);
+    let next = match row {
+        0 => ROW_STARTS[1] + col * 2,
+        1 => ROW_STARTS[2] + col / 2 * 3 + col % 2,
+        2 => ROW_STARTS[3] + col,
+        _ => return None,
+    };
+    Some([next, next + 1])
+}
+
+/// 上に重なるカードが残っていないかどうかを判定（トライピークス）
+pub fn is_exposed(index: usize, occupied: impl Fn(usize) -> bool) -> bool {
+    covering(index).is_none_or(|cover| !cover.iter().any(|index| occupied(*index)))
+}
+
+/// 捨て札の一番上に重ねられるかどうかを判定（数字が1つ違い、つなぐ場合は K と A も続く）
+pub fn can_play(top: u32, number: u32, wrap_ranks: bool) -> bool {
+    top.abs_diff(number) == 1 || (wrap_ranks && top.abs_diff(number) == 12)
+}
+
+/// 続けて重ねた枚数に応じた得点を取得
+pub fn run_score(run: u32) -> i32 {
+    run as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn row_col_splits_the_three_peaks() {
+        assert_eq!(row_col(0), (0, 0));
+        assert_eq!(row_col(2), (0, 2));
+        assert_eq!(row_col(3), (1, 0));
+        assert_eq!(row_col(8), (1, 5));
+        assert_eq!(row_col(9), (2, 0));
+        assert_eq!(row_col(17), (2, 8));
+        assert_eq!(row_col(18), (3, 0));
+        assert_eq!(row_col(NUM_PEAKS_CARDS - 1), (3, 9));
+    }
+
+    #[test]
+    fn covering_stays_inside_each_peak() {
+        assert_eq!(covering(0), Some([3, 4]));
+        assert_eq!(covering(2), Some([7, 8]));
+        assert_eq!(covering(3), Some([9, 10]));
+        assert_eq!(covering(4), Some([10, 11]));
+        assert_eq!(covering(5), Some([12, 13]));
+        assert_eq!(covering(8), Some([16, 17]));
+        assert_eq!(covering(9), Some([18, 19]));
+        assert_eq!(covering(17), Some([26, 27]));
+        assert!((18..NUM_PEAKS_CARDS).all(|index| covering(index).is_none()));
+
+        // 重なるカードは1つ下の段にある
+        for index in 0..18 {
+            let (row, _) = row_col(index);
+            let cover = covering(index).unwrap();
+            assert!(cover.iter().all(|cover| row_col(*cover).0 == row + 1));
+        }
+    }
+
+    #[test]
+    fn play_one_rank_up_or_down() {
+        assert!(can_play(5, 6, false));
+        assert!(can_play(5, 4, false));
+        assert!(!can_play(5, 5, false));
+        assert!(!can_play(5, 7, false));
+
+        // K と A は、つなぐ設定のときだけ続く
+        assert!(!can_play(13, 1, false));
+        assert!(can_play(13, 1, true));
+        assert!(can_play(1, 13, true));
+    }
+}
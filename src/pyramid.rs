@@ -0,0 +1,87 @@
+pub const NUM_PYRAMID_ROWS: usize = 7;
+pub const NUM_PYRAMID_CARDS: usize = NUM_PYRAMID_ROWS * (NUM_PYRAMID_ROWS + 1) / 2;
+pub const NUM_PYRAMID_TURN: u32 = 1;
+pub const MAX_PYRAMID_RECYCLE: u32 = 2;
+const REMOVE_SUM: u32 = 13;
+
+/// 段と段の中の位置を取得
+pub fn row_col(index: usize) -> (usize, usize) {
+    let mut row = 0;
+    while (row + 1) * (row + 2) / 2 <= index {
+        row += 1;
+    }
+    (row, index - row * (row + 1) / 2)
+}
+
+/// 上に重なるカードの位置を取得（最下段には重なるカードがない）
+pub fn covering(index: usize) -> Option<[usize; 2]> {
+    let (row, col) = row_col(index);
+    if row + 1 >= NUM_PYRAMID_ROWS {
+        return None;
+    }
+    let next = (row + 1) * (row + 2) / 2 + col;
+    Some([next, next + 1])
+}
+
+/// 上に重なるカードが残っていないかどうかを判定
+pub fn is_exposed(index: usize, occupied: impl Fn(usize) -> bool) -> bool {
+    covering(index).is_none_or(|cover| !cover.iter().any(|index| occupied(*index)))
+}
+
+/// 取り除けるかどうかを判定（合計が13になる2枚か、K 1枚）
+pub fn can_remove(numbers: &[u32]) -> bool {
+    (1..=2).contains(&numbers.len()) && numbers.iter().sum::<u32>() == REMOVE_SUM
+}
+
+/// 取り除ける組み合わせが残っているかどうかを判定
+pub fn has_removable(numbers: &[u32]) -> bool {
+    numbers.iter().enumerate().any(|(index, number)| {
+        can_remove(&[*number]) || numbers[index + 1..].iter().any(|other| can_remove(&[*number, *other]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn row_col_walks_rows_left_to_right() {
+        assert_eq!(row_col(0), (0, 0));
+        assert_eq!(row_col(1), (1, 0));
+        assert_eq!(row_col(2), (1, 1));
+        assert_eq!(row_col(3), (2, 0));
+        assert_eq!(row_col(20), (5, 5));
+        assert_eq!(row_col(21), (6, 0));
+        assert_eq!(row_col(NUM_PYRAMID_CARDS - 1), (6, 6));
+    }
+
+    #[test]
+    fn covering_is_the_two_cards_below() {
+        assert_eq!(covering(0), Some([1, 2]));
+        assert_eq!(covering(2), Some([4, 5]));
+        assert_eq!(covering(4), Some([7, 8]));
+        assert_eq!(covering(20), Some([26, 27]));
+        assert!((21..NUM_PYRAMID_CARDS).all(|index| covering(index).is_none()));
+    }
+
+    #[test]
+    fn exposed_when_covering_cards_are_gone() {
+        assert!(is_exposed(21, |_| true));
+        assert!(!is_exposed(0, |index| index == 2));
+        assert!(is_exposed(0, |index| index > 2));
+    }
+
+    #[test]
+    fn removable_pairs_sum_to_thirteen() {
+        assert!(can_remove(&[13]));
+        assert!(can_remove(&[6, 7]));
+        assert!(!can_remove(&[12]));
+        assert!(!can_remove(&[3, 4, 6]));
+
+        assert!(has_removable(&[13]));
+        assert!(has_removable(&[5, 9, 8]));
+        assert!(!has_removable(&[]));
+        assert!(!has_removable(&[1, 2, 3]));
+        assert!(!has_removable(&[6, 6]));
+    }
+}
@@ -3,6 +3,11 @@ use bevy::{
     audio::AudioSource,
     prelude::*
 };
+use serde::{Deserialize, Serialize};
+use crate::board::{CardSuit, MAX_WASTES};
+
+/// カスタムで選べるめくる枚数と見せる枚数の上限
+pub const MAX_DRAW_COUNT: u32 = 5;
 
 #[derive(Resource)]
 pub struct GameTextures {
@@ -45,8 +50,154 @@ impl GameFonts {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Resource)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Resource, Serialize)]
 pub enum GameDifficulty {
     Easy,
     Hard,
+    Custom,
+}
+
+/// 山札から一度にめくる枚数と、山札から引いたカードをずらして見せる枚数
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Resource, Serialize)]
+pub struct DrawRule {
+    pub num_turn: u32,
+    pub num_fan: u32,
+}
+
+impl Default for DrawRule {
+    fn default() -> Self {
+        Self {
+            num_turn: 1,
+            num_fan: MAX_WASTES,
+        }
+    }
+}
+
+impl DrawRule {
+    /// 難易度に応じた枚数を取得（カスタムはオプションで決めた枚数）
+    pub fn new(difficulty: GameDifficulty, options: &GameOptions) -> Self {
+        match difficulty {
+            GameDifficulty::Easy => Self {
+                num_turn: 1,
+                num_fan: MAX_WASTES,
+            },
+            GameDifficulty::Hard => Self {
+                num_turn: 3,
+                num_fan: MAX_WASTES,
+            },
+            GameDifficulty::Custom => Self {
+                num_turn: options.custom_draw.num_turn.clamp(1, MAX_DRAW_COUNT),
+                num_fan: options.custom_draw.num_fan.clamp(1, MAX_DRAW_COUNT),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Resource)]
+pub struct DealSeed {
+    pub seed: u64,
+    pub fixed: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Resource, Serialize)]
+pub enum ScoringMode {
+    #[default]
+    Standard,
+    Vegas,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum StockPasses {
+    #[default]
+    Unlimited,
+    Three,
+    One,
+}
+
+impl StockPasses {
+    /// 山札を戻せる回数を取得（None は無制限）
+    pub fn max_recycle(&self) -> Option<u32> {
+        match self {
+            StockPasses::Unlimited => None,
+            StockPasses::Three => Some(2),
+            StockPasses::One => Some(0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Resource, Serialize)]
+pub enum GameKind {
+    #[default]
+    Klondike,
+    DoubleKlondike,
+    Spider,
+    FreeCell,
+    Yukon,
+    Pyramid,
+    TriPeaks,
+    Golf,
+    Canfield,
+}
+
+impl GameKind {
+    pub const ALL: [GameKind; 9] = [
+        GameKind::Klondike,
+        GameKind::DoubleKlondike,
+        GameKind::Spider,
+        GameKind::FreeCell,
+        GameKind::Yukon,
+        GameKind::Pyramid,
+        GameKind::TriPeaks,
+        GameKind::Golf,
+        GameKind::Canfield,
+    ];
 }
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum SpiderSuits {
+    #[default]
+    One,
+    Two,
+    Four,
+}
+
+impl SpiderSuits {
+    /// 使うスートを取得
+    pub fn suits(&self) -> &'static [CardSuit] {
+        match self {
+            SpiderSuits::One => &[CardSuit::Spade],
+            SpiderSuits::Two => &[CardSuit::Spade, CardSuit::Heart],
+            SpiderSuits::Four => &CardSuit::ALL,
+        }
+    }
+}
+
+/// カードをクリックしたときの動かし方
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ClickMode {
+    #[default]
+    AutoMove,
+    SelectThenPlace,
+}
+
+#[derive(Clone, Copy, Debug, Default, Resource)]
+pub struct StockRecycle {
+    pub count: u32,
+    pub limit: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Resource, Serialize)]
+pub struct GameOptions {
+    pub winnable_only: bool,
+    pub scoring: ScoringMode,
+    pub stock_passes: StockPasses,
+    pub game: GameKind,
+    pub spider_suits: SpiderSuits,
+    pub wrap_ranks: bool,
+    pub custom_draw: DrawRule,
+    pub click_mode: ClickMode,
+}
+
+/// 遊んでいるゲームを始めたときの設定（遊んでいる途中で設定を変えても変わらない）
+#[derive(Clone, Copy, Debug, Default, Deref, Resource)]
+pub struct CurrentOptions(pub GameOptions);
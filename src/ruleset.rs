@@ -0,0 +1,1262 @@
+use bevy::prelude::*;
+use crate::board::{shuffle_deck, shuffled_deck, Board, CardSuit, NUM_PILES, NUM_SUIT};
+use crate::canfield::{NUM_CANFIELD_PILES, NUM_CANFIELD_TURN, NUM_RESERVE_CARDS};
+use crate::cardlist::CardList;
+use crate::freecell::{NUM_FREECELL_PILES, NUM_FREE_CELLS};
+use crate::game::{get_max_recycle, CardInfo, CardType, MoveStep};
+use crate::peaks::{self, NUM_GOLF_PILES, NUM_GOLF_PILE_CARDS, NUM_PEAKS_CARDS, NUM_PEAKS_TURN};
+use crate::pyramid::{self, MAX_PYRAMID_RECYCLE, NUM_PYRAMID_CARDS, NUM_PYRAMID_TURN};
+use crate::resources::{DrawRule, GameKind, GameOptions, ScoringMode, StockRecycle};
+use crate::spider::{self, NUM_SPIDER_DECKS, NUM_SPIDER_DEAL, NUM_SPIDER_PILES};
+use crate::yukon;
+
+const POSITION_PILES: [Vec2; NUM_PILES] = [
+    Vec2::new(-193., 62.),
+    Vec2::new(-129., 62.),
+    Vec2::new(-65., 62.),
+    Vec2::new(-1., 62.),
+    Vec2::new(63., 62.),
+    Vec2::new(127., 62.),
+    Vec2::new(191., 62.),
+];
+const POSITION_DOUBLE_STOCK: Vec2 = Vec2::new(-294., 63.);
+const POSITION_DOUBLE_WASTE: Vec2 = Vec2::new(-294., 1.);
+const POSITION_DOUBLE_PILES: [Vec2; NUM_DOUBLE_PILES] = [
+    Vec2::new(-230., 62.),
+    Vec2::new(-178., 62.),
+    Vec2::new(-126., 62.),
+    Vec2::new(-74., 62.),
+    Vec2::new(-22., 62.),
+    Vec2::new(30., 62.),
+    Vec2::new(82., 62.),
+    Vec2::new(134., 62.),
+    Vec2::new(186., 62.),
+];
+const POSITION_DOUBLE_FOUNDATIONS: [Vec2; NUM_DOUBLE_DECKS * NUM_SUIT] = [
+    Vec2::new(249., 62.),
+    Vec2::new(249., 2.),
+    Vec2::new(249., -58.),
+    Vec2::new(249., -118.),
+    Vec2::new(293., 62.),
+    Vec2::new(293., 2.),
+    Vec2::new(293., -58.),
+    Vec2::new(293., -118.),
+];
+const POSITION_SPIDER_PILES: [Vec2; NUM_SPIDER_PILES] = [
+    Vec2::new(-206., 62.),
+    Vec2::new(-160., 62.),
+    Vec2::new(-114., 62.),
+    Vec2::new(-68., 62.),
+    Vec2::new(-22., 62.),
+    Vec2::new(24., 62.),
+    Vec2::new(70., 62.),
+    Vec2::new(116., 62.),
+    Vec2::new(162., 62.),
+    Vec2::new(208., 62.),
+];
+const POSITION_FREECELL_PILES: [Vec2; NUM_FREECELL_PILES] = [
+    Vec2::new(-189., 62.),
+    Vec2::new(-135., 62.),
+    Vec2::new(-81., 62.),
+    Vec2::new(-27., 62.),
+    Vec2::new(27., 62.),
+    Vec2::new(81., 62.),
+    Vec2::new(135., 62.),
+    Vec2::new(189., 62.),
+];
+const POSITION_FREE_CELLS: [Vec2; NUM_FREE_CELLS] = [
+    Vec2::new(-257., 62.),
+    Vec2::new(-257., 2.),
+    Vec2::new(-257., -58.),
+    Vec2::new(-257., -118.),
+];
+const POSITION_CANFIELD_RESERVE: Vec2 = Vec2::new(-186., 62.);
+const POSITION_CANFIELD_PILES: [Vec2; NUM_CANFIELD_PILES] = [
+    Vec2::new(-112., 62.),
+    Vec2::new(-40., 62.),
+    Vec2::new(32., 62.),
+    Vec2::new(104., 62.),
+];
+const POSITION_PYRAMID_TOP: Vec2 = Vec2::new(0., 62.);
+const OFFSET_PYRAMID: Vec2 = Vec2::new(40., 24.);
+const POSITION_PEAKS_TOP: Vec2 = Vec2::new(0., 62.);
+const OFFSET_PEAKS: Vec2 = Vec2::new(40., 26.);
+
+const NUM_DOUBLE_DECKS: usize = 2;
+const NUM_DOUBLE_PILES: usize = 9;
+// ダブルクロンダイクは横幅 640 の盤面を縮小して表示する
+const DOUBLE_KLONDIKE_SCALE: f32 = 640. / 568.;
+
+const VEGAS_BUY_IN: i32 = 52;
+const SPIDER_START_SCORE: i32 = 500;
+
+/// 配るときのカードの置き場
+#[derive(Clone, Copy)]
+pub struct DealSlot {
+    pub card_type: CardType,
+    pub order: i32,
+    pub facedown: bool,
+    pub clickable: bool,
+}
+
+/// カードをクリックしたときの動作
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClickAction {
+    /// 何もしない
+    None,
+    /// 山札をめくる
+    TurnStock,
+    /// 捨て札に重ねる
+    PlayToWaste,
+    /// 合計13になる組み合わせで取り除く
+    RemovePair,
+    /// 移動先の候補に動かす
+    AutoMove,
+}
+
+/// 難易度の選択画面で変えられる設定
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GameOption {
+    /// カスタム難易度（めくる枚数、ずらして見せる枚数）
+    CustomDraw,
+    /// 勝てる配布のみ
+    WinnableOnly,
+    /// ベガス方式の得点
+    Vegas,
+    /// 山札を戻せる回数
+    StockPasses,
+    /// スートの数
+    SpiderSuits,
+    /// K と A をつなげるかどうか
+    WrapRanks,
+}
+
+/// ゲームごとのルール（置き場、配り方、移動できるかどうか、得点、クリア条件）
+pub trait Ruleset: Send + Sync {
+    /// ゲーム名を取得
+    fn name(&self) -> &'static str;
+
+    /// ルールの説明を取得
+    fn summary(&self) -> &'static str;
+
+    /// 難易度の選択画面に出す設定を行ごとに取得
+    fn option_rows(&self) -> &'static [&'static [GameOption]] {
+        &[]
+    }
+
+    /// 設定を変えられるかどうかを取得
+    fn has_option(&self, option: GameOption) -> bool {
+        self.option_rows().iter().any(|row| row.contains(&option))
+    }
+
+    /// 使うカードの枚数を取得
+    fn num_cards(&self) -> usize {
+        NUM_SUIT * 13
+    }
+
+    /// 場札の列数を取得
+    fn num_piles(&self) -> usize {
+        0
+    }
+
+    /// フリーセルの数を取得
+    fn num_free_cells(&self) -> usize {
+        0
+    }
+
+    /// 組札の置き場の数を取得
+    fn num_foundations(&self) -> usize {
+        NUM_SUIT
+    }
+
+    /// 山札を使うかどうかを取得
+    fn has_stock(&self) -> bool {
+        true
+    }
+
+    /// 盤面の表示倍率を取得（1より大きいときは広い盤面を縮小して表示）
+    fn table_scale(&self) -> f32 {
+        1.
+    }
+
+    /// ゲーム固有の置き場（場札の列、フリーセル、ピラミッドなど）の表示位置を取得
+    fn slot_position(&self, _card_type: CardType) -> Option<Vec2> {
+        None
+    }
+
+    /// ドラッグしたカードを置ける置き場を取得
+    fn drop_slots(&self) -> Vec<CardType> {
+        table_slots(self)
+    }
+
+    /// キーボードで選べる置き場を取得
+    fn cursor_slots(&self, _card_list: &CardList) -> Vec<CardType> {
+        stock_slots(self).into_iter().chain(table_slots(self)).collect()
+    }
+
+    /// 配布番号に応じたカードの並びを取得
+    fn deck(&self, seed: u64, _options: &GameOptions) -> Vec<(CardSuit, u32)> {
+        shuffled_deck(seed)
+    }
+
+    /// 配り方を取得（山札の上から順に置き場を決め、残りは山札のまま）
+    fn deal(&self) -> Vec<DealSlot>;
+
+    /// 配り終えたときの得点を取得
+    fn deal_score(&self, _scoring: ScoringMode) -> i32 {
+        0
+    }
+
+    /// 得点方式を取得
+    fn scoring(&self, _options: &GameOptions) -> ScoringMode {
+        ScoringMode::Standard
+    }
+
+    /// 山札を戻せる回数を取得（None は無制限）
+    fn max_recycle(&self, _draw: &DrawRule, _options: &GameOptions) -> Option<u32> {
+        None
+    }
+
+    /// 山札から一度にめくる枚数を取得
+    fn num_turn(&self, _draw: &DrawRule) -> u32 {
+        1
+    }
+
+    /// 組札に最初に置くカードの数字を取得
+    fn foundation_base(&self, _card_list: &CardList) -> u32 {
+        1
+    }
+
+    /// 移動先に置けるかどうかを取得（置ける場合は移動後の順番）
+    fn stack_order(&self, _card_list: &CardList, _card: &CardInfo, _dst: CardType) -> Option<i32> {
+        None
+    }
+
+    /// ドラッグで動かせるかどうかを取得
+    fn can_drag(&self, _card_list: &CardList, _card: &CardInfo) -> bool {
+        false
+    }
+
+    /// 選んでから置く操作を使えるかどうかを取得（カードを移動先に動かすゲームのみ）
+    fn can_select_then_place(&self) -> bool {
+        true
+    }
+
+    /// カードをクリックしたときの動作を取得
+    fn click_action(&self, card: &CardInfo) -> ClickAction {
+        match card.card_type {
+            CardType::Stock => ClickAction::TurnStock,
+            CardType::Waste
+            | CardType::Pile(_)
+            | CardType::Foundation(_)
+            | CardType::FreeCell(_)
+            | CardType::Reserve => ClickAction::AutoMove,
+            _ => ClickAction::None,
+        }
+    }
+
+    /// クリックで動かすときの移動先を良い順に取得
+    /// （困らない組札、裏向きのカードを表にできる列、組札、カードのある列、空いた列、フリーセルの順）
+    fn auto_move_dsts(&self, card_list: &CardList, card: &CardInfo) -> Vec<CardType> {
+        let num_foundations = self.num_foundations();
+
+        // 下のカードが裏向きなら、動かすと表にできる
+        let exposes_facedown = match card.card_type {
+            CardType::Pile(index) => card_list.get_pile_cards(index).is_some_and(|cards| {
+                cards.iter().any(|below| below.order == card.order - 1 && below.facedown)
+            }),
+            _ => false,
+        };
+        // 列に残ったカードを空いた列に移しても意味がない
+        let whole_pile = matches!(card.card_type, CardType::Pile(_)) && card.order == 0;
+
+        // 組札のカードは場札にだけ戻す
+        let num_foundation_dsts = if matches!(card.card_type, CardType::Foundation(_)) {
+            0
+        } else {
+            num_foundations
+        };
+        let mut dsts: Vec<(u32, CardType)> = (0..num_foundation_dsts as u32).map(CardType::Foundation)
+            .chain((0..self.num_piles() as u32).map(CardType::Pile))
+            .chain((0..self.num_free_cells() as u32).map(CardType::FreeCell))
+            .filter(|dst| *dst != card.card_type)
+            .filter_map(|dst| self.stack_order(card_list, card, dst).map(|order| (dst, order)))
+            .filter_map(|(dst, order)| {
+                let priority = match dst {
+                    CardType::Foundation(_) if card_list.is_safe_foundation(card, order, num_foundations) => 0,
+                    CardType::Pile(_) if exposes_facedown => 1,
+                    CardType::Foundation(_) => 2,
+                    CardType::FreeCell(_) => 5,
+                    _ if order > 0 => 3,
+                    _ if whole_pile => return None,
+                    _ => 4,
+                };
+                Some((priority, dst))
+            }).collect();
+
+        // 同じ順位のときは左の置き場から
+        dsts.sort_by_key(|(priority, _)| *priority);
+        dsts.into_iter().map(|(_, dst)| dst).collect()
+    }
+
+    /// 山札をクリックしたときに山札のカードを動かす（動かした手を返す）
+    fn turn_stock(&self, card_list: &mut CardList, draw: &DrawRule) -> Vec<MoveStep> {
+        card_list.turn_stock(self.num_turn(draw))
+    }
+
+    /// 場のカードを捨て札に重ねる（重ねた手を返す）
+    fn play_to_waste(&self, _card_list: &mut CardList, _card: Entity, _options: &GameOptions) -> Vec<MoveStep> {
+        Vec::new()
+    }
+
+    /// カードを取り除く（取り除いた手を返す）
+    fn remove_cards(&self, _card_list: &mut CardList, _cards: &[Entity]) -> Vec<MoveStep> {
+        Vec::new()
+    }
+
+    /// カードを移動したあとの処理（続けて動かした手を返す）
+    fn after_move(&self, _card_list: &mut CardList, _dst: CardType) -> Vec<MoveStep> {
+        Vec::new()
+    }
+
+    /// 盤面（Board）に移動を適用して遊ぶかどうかを取得
+    fn uses_board(&self) -> bool {
+        false
+    }
+
+    /// カードの置き場から盤面を作る（盤面を使わないゲームは None）
+    fn board(&self, card_list: &CardList, draw: &DrawRule, stock_recycle: &StockRecycle) -> Option<Board> {
+        self.uses_board().then(|| {
+            card_list.to_board(self.num_piles(), self.num_foundations(), self.num_turn(draw), stock_recycle)
+        })
+    }
+
+    /// 自動完了を使えるかどうかを取得
+    fn can_auto_complete(&self) -> bool {
+        false
+    }
+
+    /// 1手ごとの得点を取得
+    fn score(&self, _move_step: MoveStep, _scoring: ScoringMode) -> i32 {
+        0
+    }
+
+    /// 続けて重ねた枚数に応じた得点を取得
+    fn run_score(&self, _run: u32) -> i32 {
+        0
+    }
+
+    /// クリアしたかどうかを取得
+    fn is_won(&self, card_list: &CardList) -> bool {
+        card_list.0.iter().all(|card| matches!(card.card_type, CardType::Foundation(_)))
+    }
+
+    /// 手詰まりかどうかを取得（盤面を使うゲームは盤面で判定）
+    fn is_dead_end(
+        &self,
+        card_list: &CardList,
+        stock_recycle: &StockRecycle,
+        draw: &DrawRule,
+        _options: &GameOptions,
+    ) -> bool {
+        self.board(card_list, draw, stock_recycle).is_some_and(|board| board.is_dead_end())
+    }
+}
+
+impl GameKind {
+    /// ゲームのルールを取得
+    pub fn ruleset(&self) -> &'static dyn Ruleset {
+        match self {
+            GameKind::Klondike => &Klondike,
+            GameKind::DoubleKlondike => &DoubleKlondike,
+            GameKind::Spider => &Spider,
+            GameKind::FreeCell => &FreeCell,
+            GameKind::Yukon => &Yukon,
+            GameKind::Pyramid => &Pyramid,
+            GameKind::TriPeaks => &TriPeaks,
+            GameKind::Golf => &Golf,
+            GameKind::Canfield => &Canfield,
+        }
+    }
+}
+
+/// 組札に置けるかどうかを取得（同じスートを最初の数字から順に1枚ずつ、順番は最初の数字を1とする）
+fn foundation_order(card_list: &CardList, card: &CardInfo, slot: u32, base: u32) -> Option<i32> {
+    let can_stack = card.card_type != CardType::Foundation(slot)
+        && card_list.get_connected_cards(card.entity).is_none()
+        && card_list.can_stack_foundation(slot, card, base);
+    can_stack.then_some(((card.card_number + 13 - base) % 13 + 1) as i32)
+}
+
+/// 山札と山札から引いたカードの置き場を取得
+fn stock_slots<R: Ruleset + ?Sized>(ruleset: &R) -> Vec<CardType> {
+    if ruleset.has_stock() {
+        vec![CardType::Stock, CardType::Waste]
+    } else {
+        Vec::new()
+    }
+}
+
+/// フリーセル、組札、場札の列の置き場を取得
+fn table_slots<R: Ruleset + ?Sized>(ruleset: &R) -> Vec<CardType> {
+    (0..ruleset.num_free_cells() as u32).map(CardType::FreeCell)
+        .chain((0..ruleset.num_foundations() as u32).map(CardType::Foundation))
+        .chain((0..ruleset.num_piles() as u32).map(CardType::Pile))
+        .collect()
+}
+
+/// 残っているカードの置き場を取得（ピラミッド、トライピークス）
+fn remaining_slots(card_list: &CardList, is_slot: fn(CardType) -> bool) -> Vec<CardType> {
+    card_list.0.iter().map(|card| card.card_type).filter(|card_type| is_slot(*card_type)).collect()
+}
+
+/// 列ごとに配る枚数から配り方を取得（各列の裏向きのカードの上に表向きで重ねる）
+fn pile_deal_slots(counts: impl Iterator<Item = (u32, u32)>) -> Vec<DealSlot> {
+    counts.enumerate().flat_map(|(pile_index, (num_facedown, num_faceup))| {
+        (0..num_facedown + num_faceup).map(move |order| DealSlot {
+            card_type: CardType::Pile(pile_index as u32),
+            order: order as i32,
+            facedown: order < num_facedown,
+            clickable: order >= num_facedown,
+        })
+    }).collect()
+}
+
+/// 山札の一番上のカードを1枚めくった置き場を取得
+fn first_waste_slot() -> DealSlot {
+    DealSlot {
+        card_type: CardType::Waste,
+        order: 0,
+        facedown: false,
+        clickable: false,
+    }
+}
+
+/// クロンダイク
+struct Klondike;
+
+impl Ruleset for Klondike {
+    fn name(&self) -> &'static str {
+        "クロンダイク"
+    }
+
+    fn summary(&self) -> &'static str {
+        "場札を色違いで K から順に重ね、A から K までを4つの組札に集める。山札は難易度に応じて1枚か3枚ずつめくる。"
+    }
+
+    fn option_rows(&self) -> &'static [&'static [GameOption]] {
+        &[
+            &[GameOption::CustomDraw],
+            &[GameOption::WinnableOnly, GameOption::Vegas],
+            &[GameOption::StockPasses],
+        ]
+    }
+
+    fn num_piles(&self) -> usize {
+        NUM_PILES
+    }
+
+    fn slot_position(&self, card_type: CardType) -> Option<Vec2> {
+        match card_type {
+            CardType::Pile(index) => POSITION_PILES.get(index as usize).copied(),
+            _ => None,
+        }
+    }
+
+    fn deal(&self) -> Vec<DealSlot> {
+        pile_deal_slots((0..NUM_PILES as u32).map(|index| (index, 1)))
+    }
+
+    fn deal_score(&self, scoring: ScoringMode) -> i32 {
+        // ベガス方式では配るたびに参加料を払う
+        match scoring {
+            ScoringMode::Standard => 0,
+            ScoringMode::Vegas => -VEGAS_BUY_IN,
+        }
+    }
+
+    fn scoring(&self, options: &GameOptions) -> ScoringMode {
+        options.scoring
+    }
+
+    fn max_recycle(&self, draw: &DrawRule, options: &GameOptions) -> Option<u32> {
+        get_max_recycle(draw.num_turn, options.scoring, options.stock_passes)
+    }
+
+    fn num_turn(&self, draw: &DrawRule) -> u32 {
+        draw.num_turn
+    }
+
+    fn stack_order(&self, card_list: &CardList, card: &CardInfo, dst: CardType) -> Option<i32> {
+        match dst {
+            CardType::Foundation(slot) => foundation_order(card_list, card, slot, self.foundation_base(card_list)),
+            CardType::Pile(index) => card_list.can_stack_pile(index, card),
+            _ => None,
+        }
+    }
+
+    fn can_drag(&self, _card_list: &CardList, _card: &CardInfo) -> bool {
+        true
+    }
+
+    fn uses_board(&self) -> bool {
+        true
+    }
+
+    fn can_auto_complete(&self) -> bool {
+        true
+    }
+
+    fn score(&self, move_step: MoveStep, scoring: ScoringMode) -> i32 {
+        // ベガス方式では組札のカード1枚につき5ドル
+        if scoring == ScoringMode::Vegas {
+            return match move_step {
+                MoveStep::WasteToFoundation | MoveStep::PileToFoundation => 5,
+                MoveStep::FoundationToPile => -5,
+                _ => 0,
+            };
+        }
+
+        match move_step {
+            MoveStep::WasteToStock => -100,
+            MoveStep::WasteToPile => 5,
+            MoveStep::WasteToFoundation => 10,
+            MoveStep::PileToFoundation => 15,
+            MoveStep::FoundationToPile => -15,
+            MoveStep::FaceupPile => 5,
+            _ => 0,
+        }
+    }
+}
+
+/// ダブルクロンダイク
+struct DoubleKlondike;
+
+impl Ruleset for DoubleKlondike {
+    fn name(&self) -> &'static str {
+        "ダブルクロンダイク"
+    }
+
+    fn summary(&self) -> &'static str {
+        "2組のカードを使う。9列の場札を色違いで K から順に重ね、A から K までを8つの組札に集める。山札は難易度に応じて1枚か3枚ずつめくる。"
+    }
+
+    fn option_rows(&self) -> &'static [&'static [GameOption]] {
+        // 勝てる配布のみは選べない（104枚の配布はソルバーが探索の上限までに判定できない）
+        &[
+            &[GameOption::CustomDraw],
+            &[GameOption::Vegas],
+            &[GameOption::StockPasses],
+        ]
+    }
+
+    fn num_cards(&self) -> usize {
+        NUM_DOUBLE_DECKS * NUM_SUIT * 13
+    }
+
+    fn num_piles(&self) -> usize {
+        NUM_DOUBLE_PILES
+    }
+
+    fn num_foundations(&self) -> usize {
+        NUM_DOUBLE_DECKS * NUM_SUIT
+    }
+
+    fn table_scale(&self) -> f32 {
+        DOUBLE_KLONDIKE_SCALE
+    }
+
+    fn slot_position(&self, card_type: CardType) -> Option<Vec2> {
+        match card_type {
+            CardType::Stock => Some(POSITION_DOUBLE_STOCK),
+            CardType::Waste => Some(POSITION_DOUBLE_WASTE),
+            CardType::Pile(index) => POSITION_DOUBLE_PILES.get(index as usize).copied(),
+            CardType::Foundation(slot) => POSITION_DOUBLE_FOUNDATIONS.get(slot as usize).copied(),
+            _ => None,
+        }
+    }
+
+    fn deck(&self, seed: u64, _options: &GameOptions) -> Vec<(CardSuit, u32)> {
+        let mut deck = Vec::new();
+        for _ in 0..NUM_DOUBLE_DECKS {
+            for suit in CardSuit::ALL {
+                for number in 1..=13 {
+                    deck.push((suit, number));
+                }
+            }
+        }
+
+        shuffle_deck(&mut deck, seed);
+        deck
+    }
+
+    fn deal(&self) -> Vec<DealSlot> {
+        pile_deal_slots((0..NUM_DOUBLE_PILES as u32).map(|index| (index, 1)))
+    }
+
+    fn deal_score(&self, scoring: ScoringMode) -> i32 {
+        // ベガス方式の参加料は2組分
+        Klondike.deal_score(scoring) * NUM_DOUBLE_DECKS as i32
+    }
+
+    fn scoring(&self, options: &GameOptions) -> ScoringMode {
+        options.scoring
+    }
+
+    fn max_recycle(&self, draw: &DrawRule, options: &GameOptions) -> Option<u32> {
+        get_max_recycle(draw.num_turn, options.scoring, options.stock_passes)
+    }
+
+    fn num_turn(&self, draw: &DrawRule) -> u32 {
+        draw.num_turn
+    }
+
+    fn stack_order(&self, card_list: &CardList, card: &CardInfo, dst: CardType) -> Option<i32> {
+        match dst {
+            CardType::Foundation(slot) => foundation_order(card_list, card, slot, self.foundation_base(card_list)),
+            CardType::Pile(index) => card_list.can_stack_pile(index, card),
+            _ => None,
+        }
+    }
+
+    fn can_drag(&self, _card_list: &CardList, _card: &CardInfo) -> bool {
+        true
+    }
+
+    fn uses_board(&self) -> bool {
+        true
+    }
+
+    fn can_auto_complete(&self) -> bool {
+        true
+    }
+
+    fn score(&self, move_step: MoveStep, scoring: ScoringMode) -> i32 {
+        // 得点はクロンダイクと同じ
+        Klondike.score(move_step, scoring)
+    }
+}
+
+/// スパイダー
+struct Spider;
+
+impl Ruleset for Spider {
+    fn name(&self) -> &'static str {
+        "スパイダー"
+    }
+
+    fn summary(&self) -> &'static str {
+        "2組のカードを使う。場札を数字の順に重ね、同じスートで K から A まで揃えた列を取り除く。山札は各列に1枚ずつ配る。"
+    }
+
+    fn option_rows(&self) -> &'static [&'static [GameOption]] {
+        &[&[GameOption::SpiderSuits]]
+    }
+
+    fn num_cards(&self) -> usize {
+        NUM_SPIDER_DECKS * NUM_SUIT * 13
+    }
+
+    fn num_piles(&self) -> usize {
+        NUM_SPIDER_PILES
+    }
+
+    fn slot_position(&self, card_type: CardType) -> Option<Vec2> {
+        match card_type {
+            CardType::Pile(index) => POSITION_SPIDER_PILES.get(index as usize).copied(),
+            _ => None,
+        }
+    }
+
+    fn drop_slots(&self) -> Vec<CardType> {
+        (0..NUM_SPIDER_PILES as u32).map(CardType::Pile).collect()
+    }
+
+    fn cursor_slots(&self, _card_list: &CardList) -> Vec<CardType> {
+        // 山札から場札の列に直接配る
+        std::iter::once(CardType::Stock).chain(table_slots(self)).collect()
+    }
+
+    fn deck(&self, seed: u64, options: &GameOptions) -> Vec<(CardSuit, u32)> {
+        spider::shuffled_deck(seed, options.spider_suits)
+    }
+
+    fn deal(&self) -> Vec<DealSlot> {
+        // 左から順に1枚ずつ配り、各列の一番上だけを表向きにする
+        (0..NUM_SPIDER_DEAL).map(|index| {
+            let facedown = index + NUM_SPIDER_PILES < NUM_SPIDER_DEAL;
+            DealSlot {
+                card_type: CardType::Pile((index % NUM_SPIDER_PILES) as u32),
+                order: (index / NUM_SPIDER_PILES) as i32,
+                facedown,
+                clickable: !facedown,
+            }
+        }).collect()
+    }
+
+    fn deal_score(&self, _scoring: ScoringMode) -> i32 {
+        SPIDER_START_SCORE
+    }
+
+    fn stack_order(&self, card_list: &CardList, card: &CardInfo, dst: CardType) -> Option<i32> {
+        match dst {
+            CardType::Pile(index) => card_list.can_stack_spider_pile(index, card),
+            _ => None,
+        }
+    }
+
+    fn can_drag(&self, card_list: &CardList, card: &CardInfo) -> bool {
+        // 同じスートの並びになっているカードだけをまとめて動かせる
+        card_list.is_spider_run(card)
+    }
+
+    fn click_action(&self, card: &CardInfo) -> ClickAction {
+        match card.card_type {
+            CardType::Stock => ClickAction::TurnStock,
+            CardType::Pile(_) => ClickAction::AutoMove,
+            _ => ClickAction::None,
+        }
+    }
+
+    fn auto_move_dsts(&self, card_list: &CardList, card: &CardInfo) -> Vec<CardType> {
+        // 同じスートに重ねられる列、数字だけ合う列、空いた列の順（列ごと空いた列には移さない）
+        let mut dsts: Vec<(u32, CardType)> = (0..NUM_SPIDER_PILES as u32).filter_map(|index| {
+            let top = card_list.get_pile_cards(index).and_then(|cards| cards.last().map(|top| top.card_suit));
+            let priority = match top {
+                Some(suit) if suit == card.card_suit => 0,
+                Some(_) => 1,
+                None if card.order == 0 => return None,
+                None => 2,
+            };
+            Some((priority, CardType::Pile(index)))
+        }).collect();
+        dsts.sort_by_key(|(priority, _)| *priority);
+        dsts.into_iter().map(|(_, dst)| dst).collect()
+    }
+
+    fn turn_stock(&self, card_list: &mut CardList, _draw: &DrawRule) -> Vec<MoveStep> {
+        // 山札から各列に1枚ずつ配る
+        card_list.deal_spider_stock()
+    }
+
+    fn after_move(&self, card_list: &mut CardList, dst: CardType) -> Vec<MoveStep> {
+        // 揃った列を組札に移す
+        match dst {
+            CardType::Pile(index) => card_list.remove_complete_run(index),
+            _ => Vec::new(),
+        }
+    }
+
+    fn score(&self, move_step: MoveStep, _scoring: ScoringMode) -> i32 {
+        // 1手ごとに1点引かれ、列を揃えるたびに100点
+        match move_step {
+            MoveStep::StockToPile | MoveStep::PileToPile => -1,
+            MoveStep::PileToFoundation => 100,
+            _ => 0,
+        }
+    }
+}
+
+/// フリーセル
+struct FreeCell;
+
+impl Ruleset for FreeCell {
+    fn name(&self) -> &'static str {
+        "フリーセル"
+    }
+
+    fn summary(&self) -> &'static str {
+        "すべて表向きで配る。4つのフリーセルに1枚ずつ置きながら、色違いの並びを組み替えて組札に集める。"
+    }
+
+    fn num_piles(&self) -> usize {
+        NUM_FREECELL_PILES
+    }
+
+    fn num_free_cells(&self) -> usize {
+        NUM_FREE_CELLS
+    }
+
+    fn has_stock(&self) -> bool {
+        false
+    }
+
+    fn slot_position(&self, card_type: CardType) -> Option<Vec2> {
+        match card_type {
+            CardType::Pile(index) => POSITION_FREECELL_PILES.get(index as usize).copied(),
+            CardType::FreeCell(index) => POSITION_FREE_CELLS.get(index as usize).copied(),
+            _ => None,
+        }
+    }
+
+    fn deal(&self) -> Vec<DealSlot> {
+        // 左から順に1枚ずつ、すべて表向きで配る
+        (0..self.num_cards()).map(|index| DealSlot {
+            card_type: CardType::Pile((index % NUM_FREECELL_PILES) as u32),
+            order: (index / NUM_FREECELL_PILES) as i32,
+            facedown: false,
+            clickable: true,
+        }).collect()
+    }
+
+    fn stack_order(&self, card_list: &CardList, card: &CardInfo, dst: CardType) -> Option<i32> {
+        match dst {
+            CardType::Foundation(slot) => foundation_order(card_list, card, slot, self.foundation_base(card_list)),
+            CardType::Pile(index) => card_list.can_stack_freecell_pile(index, card),
+            CardType::FreeCell(index) => {
+                let can_stack = !matches!(card.card_type, CardType::FreeCell(_))
+                    && card_list.get_connected_cards(card.entity).is_none()
+                    && card_list.get_free_cell_card(index).is_none();
+                can_stack.then_some(0)
+            }
+            _ => None,
+        }
+    }
+
+    fn can_drag(&self, card_list: &CardList, card: &CardInfo) -> bool {
+        // 色が交互の並びになっているカードだけをまとめて動かせる
+        card_list.is_freecell_run(card)
+    }
+
+    fn score(&self, move_step: MoveStep, _scoring: ScoringMode) -> i32 {
+        // 組札にカードを移すたびに10点
+        match move_step {
+            MoveStep::PileToFoundation | MoveStep::FreeCellToFoundation => 10,
+            _ => 0,
+        }
+    }
+}
+
+/// ユーコン
+struct Yukon;
+
+impl Ruleset for Yukon {
+    fn name(&self) -> &'static str {
+        "ユーコン"
+    }
+
+    fn summary(&self) -> &'static str {
+        "山札はない。表向きのカードは並びに関係なく上に重なったカードごと動かせる。色違いで重ねて組札に集める。"
+    }
+
+    fn num_piles(&self) -> usize {
+        NUM_PILES
+    }
+
+    fn has_stock(&self) -> bool {
+        false
+    }
+
+    fn slot_position(&self, card_type: CardType) -> Option<Vec2> {
+        Klondike.slot_position(card_type)
+    }
+
+    fn deal(&self) -> Vec<DealSlot> {
+        // 山札は残さず、1列目以外は裏向きのカードの上に5枚ずつ表向きで配る
+        pile_deal_slots((0..NUM_PILES as u32).map(yukon::deal_counts))
+    }
+
+    fn stack_order(&self, card_list: &CardList, card: &CardInfo, dst: CardType) -> Option<i32> {
+        match dst {
+            CardType::Foundation(slot) => foundation_order(card_list, card, slot, self.foundation_base(card_list)),
+            // 上に重なったカードは並びに関係なくまとめて動かせるため、同じ列は除く
+            CardType::Pile(index) if card.card_type != dst => card_list.can_stack_pile(index, card),
+            _ => None,
+        }
+    }
+
+    fn can_drag(&self, _card_list: &CardList, _card: &CardInfo) -> bool {
+        true
+    }
+}
+
+/// ピラミッド
+struct Pyramid;
+
+impl Ruleset for Pyramid {
+    fn name(&self) -> &'static str {
+        "ピラミッド"
+    }
+
+    fn summary(&self) -> &'static str {
+        "合計が13になる2枚（K は1枚）を取り除いてピラミッドを崩す。山札は2回まで戻せる。"
+    }
+
+    fn cursor_slots(&self, card_list: &CardList) -> Vec<CardType> {
+        // ピラミッドのカードは残っているところだけ選べる
+        let pyramid = remaining_slots(card_list, |card_type| matches!(card_type, CardType::Pyramid(_)));
+        stock_slots(self).into_iter().chain(table_slots(self)).chain(pyramid).collect()
+    }
+
+    fn slot_position(&self, card_type: CardType) -> Option<Vec2> {
+        let CardType::Pyramid(index) = card_type else {
+            return None;
+        };
+        let (row, col) = pyramid::row_col(index as usize);
+        Some(Vec2::new(
+            POSITION_PYRAMID_TOP.x + (col as f32 - row as f32 / 2.) * OFFSET_PYRAMID.x,
+            POSITION_PYRAMID_TOP.y - row as f32 * OFFSET_PYRAMID.y,
+        ))
+    }
+
+    fn deal(&self) -> Vec<DealSlot> {
+        // 上の段から順に表向きで並べ、残りは山札にする
+        (0..NUM_PYRAMID_CARDS).map(|index| DealSlot {
+            card_type: CardType::Pyramid(index as u32),
+            order: index as i32,
+            facedown: false,
+            clickable: pyramid::covering(index).is_none(),
+        }).collect()
+    }
+
+    fn max_recycle(&self, _draw: &DrawRule, _options: &GameOptions) -> Option<u32> {
+        Some(MAX_PYRAMID_RECYCLE)
+    }
+
+    fn can_select_then_place(&self) -> bool {
+        false
+    }
+
+    fn click_action(&self, card: &CardInfo) -> ClickAction {
+        match card.card_type {
+            CardType::Stock => ClickAction::TurnStock,
+            CardType::Waste | CardType::Pyramid(_) => ClickAction::RemovePair,
+            _ => ClickAction::None,
+        }
+    }
+
+    fn num_turn(&self, _draw: &DrawRule) -> u32 {
+        NUM_PYRAMID_TURN
+    }
+
+    fn remove_cards(&self, card_list: &mut CardList, cards: &[Entity]) -> Vec<MoveStep> {
+        // K は1枚で、それ以外は2枚の合計が13になれば取り除ける
+        let numbers: Vec<u32> = cards.iter().filter_map(|entity| card_list.get(*entity)).map(|card| card.card_number).collect();
+        if numbers.len() != cards.len() || !pyramid::can_remove(&numbers) {
+            return Vec::new();
+        }
+        card_list.remove_pyramid_cards(cards)
+    }
+
+    fn score(&self, move_step: MoveStep, _scoring: ScoringMode) -> i32 {
+        // 取り除くたびに5点
+        match move_step {
+            MoveStep::RemoveCards => 5,
+            _ => 0,
+        }
+    }
+
+    fn is_won(&self, card_list: &CardList) -> bool {
+        // ピラミッドのカードをすべて取り除いたらクリア
+        !card_list.0.iter().any(|card| matches!(card.card_type, CardType::Pyramid(_)))
+    }
+
+    fn is_dead_end(
+        &self,
+        card_list: &CardList,
+        stock_recycle: &StockRecycle,
+        _draw: &DrawRule,
+        _options: &GameOptions,
+    ) -> bool {
+        if self.is_won(card_list) {
+            return false;
+        }
+
+        let has_stock = card_list.0.iter().any(|card| card.card_type == CardType::Stock);
+        let has_waste = card_list.0.iter().any(|card| card.card_type == CardType::Waste);
+        let can_recycle = stock_recycle.limit.is_none_or(|limit| stock_recycle.count < limit);
+        if has_stock || (has_waste && can_recycle) {
+            return false;
+        }
+
+        // 取り除けるカードの組み合わせが残っていなければ手詰まり
+        let numbers: Vec<u32> = card_list.0.iter().filter(|card| {
+            card.clickable && matches!(card.card_type, CardType::Pyramid(_) | CardType::Waste)
+        }).map(|card| card.card_number).collect();
+        !pyramid::has_removable(&numbers)
+    }
+}
+
+/// トライピークス
+struct TriPeaks;
+
+impl Ruleset for TriPeaks {
+    fn name(&self) -> &'static str {
+        "トライピークス"
+    }
+
+    fn summary(&self) -> &'static str {
+        "捨て札と数字が1つ違うカードを重ねて3つの山を崩す。続けて重ねるほど得点が増える。"
+    }
+
+    fn option_rows(&self) -> &'static [&'static [GameOption]] {
+        &[&[GameOption::WrapRanks]]
+    }
+
+    fn num_foundations(&self) -> usize {
+        0
+    }
+
+    fn cursor_slots(&self, card_list: &CardList) -> Vec<CardType> {
+        // 山のカードは残っているところだけ選べる
+        let peaks = remaining_slots(card_list, |card_type| matches!(card_type, CardType::Peak(_)));
+        stock_slots(self).into_iter().chain(peaks).collect()
+    }
+
+    fn slot_position(&self, card_type: CardType) -> Option<Vec2> {
+        let CardType::Peak(index) = card_type else {
+            return None;
+        };
+        let (row, _) = peaks::row_col(index as usize);
+        Some(Vec2::new(
+            POSITION_PEAKS_TOP.x + peaks::column_x(index as usize) * OFFSET_PEAKS.x,
+            POSITION_PEAKS_TOP.y - row as f32 * OFFSET_PEAKS.y,
+        ))
+    }
+
+    fn deal(&self) -> Vec<DealSlot> {
+        // 3つの山を並べ、上に重なるカードのない一番下の段だけを表向きにする
+        (0..NUM_PEAKS_CARDS).map(|index| {
+            let exposed = peaks::covering(index).is_none();
+            DealSlot {
+                card_type: CardType::Peak(index as u32),
+                order: index as i32,
+                facedown: !exposed,
+                clickable: exposed,
+            }
+        }).chain(std::iter::once(first_waste_slot())).collect()
+    }
+
+    fn max_recycle(&self, _draw: &DrawRule, _options: &GameOptions) -> Option<u32> {
+        Some(0)
+    }
+
+    fn can_select_then_place(&self) -> bool {
+        false
+    }
+
+    fn click_action(&self, card: &CardInfo) -> ClickAction {
+        match card.card_type {
+            CardType::Stock => ClickAction::TurnStock,
+            CardType::Peak(_) | CardType::Pile(_) => ClickAction::PlayToWaste,
+            _ => ClickAction::None,
+        }
+    }
+
+    fn num_turn(&self, _draw: &DrawRule) -> u32 {
+        NUM_PEAKS_TURN
+    }
+
+    fn play_to_waste(&self, card_list: &mut CardList, card: Entity, options: &GameOptions) -> Vec<MoveStep> {
+        card_list.play_peaks_card(card, options.wrap_ranks)
+    }
+
+    fn run_score(&self, run: u32) -> i32 {
+        // 続けて重ねるほど1枚あたりの得点が増える
+        peaks::run_score(run)
+    }
+
+    fn is_won(&self, card_list: &CardList) -> bool {
+        !card_list.0.iter().any(|card| matches!(card.card_type, CardType::Peak(_)))
+    }
+
+    fn is_dead_end(
+        &self,
+        card_list: &CardList,
+        _stock_recycle: &StockRecycle,
+        _draw: &DrawRule,
+        options: &GameOptions,
+    ) -> bool {
+        is_peaks_dead_end(card_list, options.wrap_ranks)
+    }
+}
+
+/// ゴルフ
+struct Golf;
+
+impl Ruleset for Golf {
+    fn name(&self) -> &'static str {
+        "ゴルフ"
+    }
+
+    fn summary(&self) -> &'static str {
+        "捨て札と数字が1つ違う列の一番上のカードを重ねて、7つの列をすべて崩す。続けて重ねるほど得点が増える。"
+    }
+
+    fn option_rows(&self) -> &'static [&'static [GameOption]] {
+        &[&[GameOption::WrapRanks]]
+    }
+
+    fn num_piles(&self) -> usize {
+        NUM_GOLF_PILES
+    }
+
+    fn num_foundations(&self) -> usize {
+        0
+    }
+
+    fn slot_position(&self, card_type: CardType) -> Option<Vec2> {
+        Klondike.slot_position(card_type)
+    }
+
+    fn deal(&self) -> Vec<DealSlot> {
+        // 7列に5枚ずつ表向きで配る
+        (0..NUM_GOLF_PILES * NUM_GOLF_PILE_CARDS).map(|index| {
+            let order = index / NUM_GOLF_PILES;
+            DealSlot {
+                card_type: CardType::Pile((index % NUM_GOLF_PILES) as u32),
+                order: order as i32,
+                facedown: false,
+                clickable: order + 1 == NUM_GOLF_PILE_CARDS,
+            }
+        }).chain(std::iter::once(first_waste_slot())).collect()
+    }
+
+    fn max_recycle(&self, _draw: &DrawRule, _options: &GameOptions) -> Option<u32> {
+        Some(0)
+    }
+
+    fn can_select_then_place(&self) -> bool {
+        false
+    }
+
+    fn click_action(&self, card: &CardInfo) -> ClickAction {
+        match card.card_type {
+            CardType::Stock => ClickAction::TurnStock,
+            CardType::Peak(_) | CardType::Pile(_) => ClickAction::PlayToWaste,
+            _ => ClickAction::None,
+        }
+    }
+
+    fn num_turn(&self, _draw: &DrawRule) -> u32 {
+        NUM_PEAKS_TURN
+    }
+
+    fn play_to_waste(&self, card_list: &mut CardList, card: Entity, options: &GameOptions) -> Vec<MoveStep> {
+        card_list.play_peaks_card(card, options.wrap_ranks)
+    }
+
+    fn run_score(&self, run: u32) -> i32 {
+        // 続けて重ねるほど1枚あたりの得点が増える
+        peaks::run_score(run)
+    }
+
+    fn is_won(&self, card_list: &CardList) -> bool {
+        !card_list.0.iter().any(|card| matches!(card.card_type, CardType::Pile(_)))
+    }
+
+    fn is_dead_end(
+        &self,
+        card_list: &CardList,
+        _stock_recycle: &StockRecycle,
+        _draw: &DrawRule,
+        options: &GameOptions,
+    ) -> bool {
+        is_peaks_dead_end(card_list, options.wrap_ranks)
+    }
+}
+
+/// キャンフィールド
+struct Canfield;
+
+impl Ruleset for Canfield {
+    fn name(&self) -> &'static str {
+        "キャンフィールド"
+    }
+
+    fn summary(&self) -> &'static str {
+        "13枚の予備札と4列の場札で遊ぶ。最初に配った組札のカードの数字から順に、K の次は A に戻って組札に集める。空いた列には予備札を補充する。山札は3枚ずつめくる。"
+    }
+
+    fn num_piles(&self) -> usize {
+        NUM_CANFIELD_PILES
+    }
+
+    fn slot_position(&self, card_type: CardType) -> Option<Vec2> {
+        match card_type {
+            CardType::Reserve => Some(POSITION_CANFIELD_RESERVE),
+            CardType::Pile(index) => POSITION_CANFIELD_PILES.get(index as usize).copied(),
+            _ => None,
+        }
+    }
+
+    fn cursor_slots(&self, _card_list: &CardList) -> Vec<CardType> {
+        stock_slots(self).into_iter().chain(std::iter::once(CardType::Reserve)).chain(table_slots(self)).collect()
+    }
+
+    fn deal(&self) -> Vec<DealSlot> {
+        // 予備札は一番上だけ表向きにし、組札に1枚、各列に1枚ずつ表向きで配る
+        let reserve = (0..NUM_RESERVE_CARDS).map(|order| DealSlot {
+            card_type: CardType::Reserve,
+            order: order as i32,
+            facedown: order + 1 < NUM_RESERVE_CARDS,
+            clickable: order + 1 == NUM_RESERVE_CARDS,
+        });
+        let foundation = DealSlot {
+            card_type: CardType::Foundation(0),
+            order: 1,
+            facedown: false,
+            clickable: false,
+        };
+        reserve
+            .chain(std::iter::once(foundation))
+            .chain(pile_deal_slots((0..NUM_CANFIELD_PILES as u32).map(|_| (0, 1))))
+            .collect()
+    }
+
+    fn num_turn(&self, _draw: &DrawRule) -> u32 {
+        NUM_CANFIELD_TURN
+    }
+
+    fn foundation_base(&self, card_list: &CardList) -> u32 {
+        // 最初に組札に配ったカードの数字から始める
+        card_list.0.iter().find(|card| {
+            matches!(card.card_type, CardType::Foundation(_)) && card.order == 1
+        }).map_or(1, |card| card.card_number)
+    }
+
+    fn stack_order(&self, card_list: &CardList, card: &CardInfo, dst: CardType) -> Option<i32> {
+        // 組札に置いたカードは戻せない
+        if matches!(card.card_type, CardType::Foundation(_)) {
+            return None;
+        }
+        match dst {
+            CardType::Foundation(slot) => foundation_order(card_list, card, slot, self.foundation_base(card_list)),
+            CardType::Pile(index) => card_list.can_stack_canfield_pile(index, card),
+            _ => None,
+        }
+    }
+
+    fn can_drag(&self, _card_list: &CardList, card: &CardInfo) -> bool {
+        !matches!(card.card_type, CardType::Foundation(_))
+    }
+
+    fn after_move(&self, card_list: &mut CardList, _dst: CardType) -> Vec<MoveStep> {
+        // 空いた列に予備札を補充する
+        card_list.fill_canfield_piles()
+    }
+
+    fn score(&self, move_step: MoveStep, _scoring: ScoringMode) -> i32 {
+        // 組札に移したカード1枚につき5点
+        match move_step {
+            MoveStep::WasteToFoundation | MoveStep::PileToFoundation | MoveStep::ReserveToFoundation => 5,
+            _ => 0,
+        }
+    }
+}
+
+/// 手詰まりかどうかを取得（トライピークス、ゴルフ）
+fn is_peaks_dead_end(card_list: &CardList, wrap_ranks: bool) -> bool {
+    let tableau: Vec<&CardInfo> = card_list.0.iter().filter(|card| {
+        matches!(card.card_type, CardType::Peak(_) | CardType::Pile(_))
+    }).collect();
+    if tableau.is_empty() || card_list.0.iter().any(|card| card.card_type == CardType::Stock) {
+        return false;
+    }
+
+    // 山札がなくなり、捨て札に重ねられるカードもなければ手詰まり
+    let top = card_list.get_waste_cards().and_then(|cards| cards.last().map(|card| card.card_number));
+    !tableau.iter().any(|card| {
+        card.clickable && top.is_some_and(|top| peaks::can_play(top, card.card_number, wrap_ranks))
+    })
+}
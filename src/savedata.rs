@@ -0,0 +1,287 @@
+use std::{fs, path::PathBuf};
+use bevy::{
+    app::{App, AppExit, Last, Plugin, Startup, Update},
+    prelude::*,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use crate::{
+    board::CardSuit,
+    cardlist::CardList,
+    game::{
+        CardType,
+        DealEvent,
+        GameClearEvent,
+        GameOverEvent,
+        MoveOneStepEvent,
+        RedoEvent,
+        UndoEvent,
+    },
+    information::{self, Bank, Movement, Score},
+    resources::{CurrentOptions, DealSeed, DrawRule, GameDifficulty, GameOptions, StockRecycle},
+    GameState,
+};
+
+const SAVE_DIRECTORY: &str = "bevysolitaire";
+const SAVE_GAME: &str = "savegame.json";
+const SAVE_OPTIONS: &str = "options.json";
+const SAVE_BANK: &str = "bank.json";
+
+#[derive(Deserialize, Serialize)]
+pub struct SavedCard {
+    pub card_suit: CardSuit,
+    pub card_number: u32,
+    pub deck: u32,
+    pub card_type: CardType,
+    pub order: i32,
+    pub facedown: bool,
+    pub clickable: bool,
+}
+
+#[derive(Deserialize, Resource, Serialize)]
+pub struct SavedGame {
+    pub difficulty: GameDifficulty,
+    pub seed: u64,
+    pub cards: Vec<SavedCard>,
+    pub time: f32,
+    pub score: i32,
+    pub movement: u32,
+    pub num_recycle: u32,
+    pub draw: DrawRule,
+    /// ゲームを始めたときの設定
+    pub options: GameOptions,
+}
+
+#[derive(Component)]
+struct SaveGame;
+
+pub struct SaveDataPlugin;
+
+impl Plugin for SaveDataPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, init_system)
+            .add_systems(Update, save_system.run_if(in_state(GameState::Play)))
+            .add_systems(Last, exit_system);
+    }
+}
+
+fn init_system(
+    mut commands: Commands,
+) {
+    commands.insert_resource(load::<GameOptions>(SAVE_OPTIONS).unwrap_or_default());
+    commands.add_observer(on_deal);
+    commands.add_observer(on_move_one_step);
+    commands.add_observer(on_undo);
+    commands.add_observer(on_redo);
+    commands.add_observer(on_game_clear);
+    commands.add_observer(on_game_over);
+}
+
+/// 移動後の保存
+fn save_system(
+    mut commands: Commands,
+    card_list: Res<CardList>,
+    difficulty: Option<Res<GameDifficulty>>,
+    draw: Res<DrawRule>,
+    deal_seed: Res<DealSeed>,
+    options: Res<CurrentOptions>,
+    stock_recycle: Res<StockRecycle>,
+    time: Res<information::Time>,
+    score: Res<Score>,
+    movement: Res<Movement>,
+    query: Query<(Entity, Ref<SaveGame>)>,
+) {
+    let mut should_save = false;
+    for (entity, save_game) in query.iter() {
+        // スコアと移動回数の反映を待つため、追加されたフレームでは保存しない
+        if save_game.is_added() {
+            continue;
+        }
+        should_save = true;
+        commands.entity(entity).despawn();
+    }
+
+    if let (true, Some(difficulty)) = (should_save, difficulty) {
+        save_game(&card_list, SavedGame {
+            difficulty: *difficulty,
+            seed: deal_seed.seed,
+            cards: Vec::new(),
+            time: time.0,
+            score: score.0,
+            movement: movement.0,
+            num_recycle: stock_recycle.count,
+            draw: *draw,
+            options: options.0,
+        });
+    }
+}
+
+/// 終了時の保存
+fn exit_system(
+    mut exit: EventReader<AppExit>,
+    state: Res<State<GameState>>,
+    card_list: Option<Res<CardList>>,
+    difficulty: Option<Res<GameDifficulty>>,
+    draw: Option<Res<DrawRule>>,
+    deal_seed: Option<Res<DealSeed>>,
+    options: Option<Res<CurrentOptions>>,
+    stock_recycle: Option<Res<StockRecycle>>,
+    time: Option<Res<information::Time>>,
+    score: Option<Res<Score>>,
+    movement: Option<Res<Movement>>,
+) {
+    if exit.is_empty() {
+        return;
+    }
+    exit.clear();
+
+    if *state != GameState::Play && *state != GameState::NewGame {
+        return;
+    }
+
+    if let (
+        Some(card_list),
+        Some(difficulty),
+        Some(draw),
+        Some(deal_seed),
+        Some(options),
+        Some(stock_recycle),
+        Some(time),
+        Some(score),
+        Some(movement),
+    ) = (card_list, difficulty, draw, deal_seed, options, stock_recycle, time, score, movement) {
+        save_game(&card_list, SavedGame {
+            difficulty: *difficulty,
+            seed: deal_seed.seed,
+            cards: Vec::new(),
+            time: time.0,
+            score: score.0,
+            movement: movement.0,
+            num_recycle: stock_recycle.count,
+            draw: *draw,
+            options: options.0,
+        });
+    }
+}
+
+fn on_deal(
+    _trigger: Trigger<DealEvent>,
+    mut commands: Commands,
+) {
+    commands.spawn(SaveGame);
+}
+
+fn on_move_one_step(
+    _trigger: Trigger<MoveOneStepEvent>,
+    mut commands: Commands,
+) {
+    commands.spawn(SaveGame);
+}
+
+fn on_undo(
+    _trigger: Trigger<UndoEvent>,
+    mut commands: Commands,
+) {
+    commands.spawn(SaveGame);
+}
+
+fn on_redo(
+    _trigger: Trigger<RedoEvent>,
+    mut commands: Commands,
+) {
+    commands.spawn(SaveGame);
+}
+
+fn on_game_clear(
+    _trigger: Trigger<GameClearEvent>,
+) {
+    remove(SAVE_GAME);
+}
+
+fn on_game_over(
+    _trigger: Trigger<GameOverEvent>,
+) {
+    remove(SAVE_GAME);
+}
+
+/// 途中のゲームを保存（カード以外は saved_game の内容を使う）
+fn save_game(
+    card_list: &CardList,
+    mut saved_game: SavedGame,
+) {
+    saved_game.cards = card_list.0.iter().map(|card| {
+        SavedCard {
+            card_suit: card.card_suit,
+            card_number: card.card_number,
+            deck: card.deck,
+            card_type: card.card_type,
+            order: card.order,
+            facedown: card.facedown,
+            clickable: card.clickable,
+        }
+    }).collect();
+
+    save(SAVE_GAME, &saved_game);
+}
+
+/// 途中のゲームを読み込み
+pub fn load_game() -> Option<SavedGame> {
+    load(SAVE_GAME)
+}
+
+/// 所持金を読み込み
+pub fn load_bank() -> Bank {
+    load(SAVE_BANK).unwrap_or_default()
+}
+
+/// 所持金を保存
+pub fn save_bank(bank: &Bank) {
+    save(SAVE_BANK, bank);
+}
+
+/// 設定を保存
+pub fn save_options(options: &GameOptions) {
+    save(SAVE_OPTIONS, options);
+}
+
+/// 保存先のパスを取得
+fn get_path(file_name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(SAVE_DIRECTORY).join(file_name))
+}
+
+/// ファイルから読み込み
+pub fn load<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    let path = get_path(file_name)?;
+    let text = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// ファイルに保存
+pub fn save<T: Serialize>(file_name: &str, data: &T) {
+    let Some(path) = get_path(file_name) else {
+        return;
+    };
+
+    let result = serde_json::to_string(data)
+        .map_err(|err| err.to_string())
+        .and_then(|text| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+            }
+            fs::write(&path, text).map_err(|err| err.to_string())
+        });
+    if let Err(err) = result {
+        warn!("failed to save {}: {}", file_name, err);
+    }
+}
+
+/// ファイルを削除
+pub fn remove(file_name: &str) {
+    if let Some(path) = get_path(file_name) {
+        if path.exists() {
+            if let Err(err) = fs::remove_file(path) {
+                warn!("failed to remove {}: {}", file_name, err);
+            }
+        }
+    }
+}
@@ -1,24 +1,71 @@
 use bevy::{
-    app::{App, Plugin},
+    app::{App, Plugin, Update},
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
     prelude::*,
     ui::widget::NodeImageMode,
 };
 use crate::{
-    game::{CancelNewGameEvent, DifficultySelectEvent},
+    game::{
+        CancelNewGameEvent,
+        ChangeGameEvent,
+        ContinueEvent,
+        DifficultySelectEvent,
+        PressButtonEvent,
+        ShowStatsEvent,
+    },
+    menuinput::{Menu, MenuFocus},
     resources::{
+        DealSeed,
+        DrawRule,
         GameDifficulty,
         GameFonts,
+        GameOptions,
         GameTextures,
+        ScoringMode,
+        MAX_DRAW_COUNT,
+        SpiderSuits,
+        StockPasses,
     },
+    ruleset::GameOption,
+    savedata,
     GameState,
 };
 
 #[derive(Component)]
 struct DifficultyButton(pub GameDifficulty);
 
+#[derive(Component)]
+struct SeedInput(String);
+
+#[derive(Component)]
+struct WinnableOnlyText;
+
+#[derive(Component)]
+struct VegasText;
+
+#[derive(Component)]
+struct StockPassesText;
+
+#[derive(Component)]
+struct CustomTurnText;
+
+#[derive(Component)]
+struct CustomFanText;
+
+#[derive(Component)]
+struct SpiderSuitsText;
+
+#[derive(Component)]
+struct WrapRanksText;
+
 #[derive(Component)]
 pub struct UISelectDifficulty;
 
+const MAX_SEED_DIGITS: usize = 20;
+
 pub struct SelectDifficultyPlugin;
 
 impl Plugin for SelectDifficultyPlugin {
@@ -29,7 +76,18 @@ impl Plugin for SelectDifficultyPlugin {
             .add_systems(OnEnter(GameState::GameClear), spawn_ui)
             .add_systems(OnExit(GameState::GameClear), despawn_ui)
             .add_systems(OnEnter(GameState::NewGame), spawn_ui)
-            .add_systems(OnExit(GameState::NewGame), despawn_ui);
+            .add_systems(OnExit(GameState::NewGame), despawn_ui)
+            .add_systems(OnEnter(GameState::GameOver), spawn_ui)
+            .add_systems(OnExit(GameState::GameOver), despawn_ui)
+            .add_systems(
+                Update,
+                seed_input_system.run_if(
+                    in_state(GameState::SelectDifficulty)
+                    .or(in_state(GameState::NewGame))
+                    .or(in_state(GameState::GameClear))
+                    .or(in_state(GameState::GameOver))
+                )
+            );
     }
 }
 
@@ -38,13 +96,25 @@ fn spawn_ui(
     state: Res<State<GameState>>,
     game_textures: Res<GameTextures>,
     game_fonts: Res<GameFonts>,
+    options: Res<GameOptions>,
 ) {
     let visibility_close_button =
-        if *state == GameState::SelectDifficulty || *state == GameState::GameClear {
+        if *state != GameState::NewGame {
             Visibility::Hidden
         } else {
             Visibility::Visible
         };
+    let has_saved_game =
+        *state == GameState::SelectDifficulty && savedata::load_game().is_some();
+    let can_replay = *state != GameState::SelectDifficulty;
+    let (window_height, button_top, game_kind_bottom) = if has_saved_game || can_replay {
+        (276., -68., 128.)
+    } else {
+        (240., -50., 92.)
+    };
+    // 設定が2行以上あるゲームは増えた行の分だけ広げる
+    let option_rows = options.game.ruleset().option_rows();
+    let custom_height = option_rows.len().saturating_sub(1) as f32 * 12.;
 
     commands
         .spawn((
@@ -58,6 +128,7 @@ fn spawn_ui(
             ZIndex(1),
             BackgroundColor(Color::srgba_u8(0, 0, 0, 230)),
             UISelectDifficulty,
+            Menu,
         ))
         .with_children(|parent| {
             let slicer = TextureSlicer {
@@ -75,7 +146,7 @@ fn spawn_ui(
                     },
                     Node {
                         width: Val::Px(226.),
-                        height: Val::Px(128.),
+                        height: Val::Px(window_height + custom_height),
                         justify_content: JustifyContent::Center,
                         align_items: AlignItems::Center,
                         ..Default::default()
@@ -97,6 +168,26 @@ fn spawn_ui(
                             visibility_close_button,
                         ))
                         .observe(on_click_close);
+                    parent
+                        .spawn((
+                            Button,
+                            Node {
+                                position_type: PositionType::Absolute,
+                                top: Val::Px(12.),
+                                left: Val::Px(12.),
+                                ..Default::default()
+                            },
+                        ))
+                        .with_child((
+                            Text::new("成績"),
+                            TextFont {
+                                font: game_fonts.dot_gothic.clone(),
+                                font_size: 12.,
+                                ..Default::default()
+                            },
+                            TextColor(Color::srgb_u8(128, 128, 128)),
+                        ))
+                        .observe(on_press_stats);
                     parent
                         .spawn((
                             Text::new("難易度を選択"),
@@ -120,12 +211,13 @@ fn spawn_ui(
                             Node {
                                 width: Val::Px(100.),
                                 height: Val::Px(40.),
-                                top: Val::Px(30.),
+                                top: Val::Px(button_top - custom_height / 2.),
                                 justify_content: JustifyContent::Center,
                                 align_items: AlignItems::Center,
                                 ..Default::default()
                             },
                             DifficultyButton(GameDifficulty::Easy),
+                            MenuFocus,
                         ))
                         .with_child((
                             Text::new("簡単"),
@@ -136,14 +228,14 @@ fn spawn_ui(
                             },
                             TextColor(Color::BLACK),
                         ))
-                        .observe(on_click_difficulty);
+                        .observe(on_press_difficulty);
                     parent
                         .spawn((
                             Button,
                             Node {
                                 width: Val::Px(100.),
                                 height: Val::Px(40.),
-                                top: Val::Px(30.),
+                                top: Val::Px(button_top - custom_height / 2.),
                                 justify_content: JustifyContent::Center,
                                 align_items: AlignItems::Center,
                                 ..Default::default()
@@ -159,7 +251,116 @@ fn spawn_ui(
                             },
                             TextColor(Color::BLACK),
                         ))
-                        .observe(on_click_difficulty);
+                        .observe(on_press_difficulty);
+                    if has_saved_game || can_replay {
+                        let label = if has_saved_game {
+                            "続きから"
+                        } else {
+                            "同じ配布で遊ぶ"
+                        };
+                        let mut button = parent.spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(160.),
+                                height: Val::Px(32.),
+                                position_type: PositionType::Absolute,
+                                bottom: Val::Px(92. + custom_height),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                        ));
+                        button.with_child((
+                            Text::new(label),
+                            TextFont {
+                                font: game_fonts.dot_gothic.clone(),
+                                font_size: 16.,
+                                ..Default::default()
+                            },
+                            TextColor(Color::BLACK),
+                        ));
+                        if has_saved_game {
+                            button.observe(on_press_continue);
+                        } else {
+                            button.observe(on_press_replay);
+                        }
+                    }
+                    parent
+                        .spawn((
+                            Button,
+                            Node {
+                                position_type: PositionType::Absolute,
+                                bottom: Val::Px(game_kind_bottom + custom_height),
+                                ..Default::default()
+                            },
+                        ))
+                        .with_child((
+                            Text::new(format!("ゲーム: {}（変更）", options.game.ruleset().name())),
+                            TextFont {
+                                font: game_fonts.dot_gothic.clone(),
+                                font_size: 12.,
+                                ..Default::default()
+                            },
+                            TextColor(Color::srgb_u8(128, 128, 128)),
+                        ))
+                        .observe(on_press_change_game);
+                    // 選んだゲームのオプション
+                    for (row, row_options) in option_rows.iter().enumerate() {
+                        parent
+                            .spawn(Node {
+                                position_type: PositionType::Absolute,
+                                bottom: Val::Px(62. + custom_height - row as f32 * 24.),
+                                column_gap: Val::Px(12.),
+                                ..Default::default()
+                            })
+                            .with_children(|parent| {
+                                for option in row_options.iter() {
+                                    spawn_option_buttons(parent, &game_fonts, &options, *option);
+                                }
+                            });
+                    }
+                    parent
+                        .spawn(Node {
+                            position_type: PositionType::Absolute,
+                            bottom: Val::Px(12.),
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(6.),
+                            ..Default::default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                Text::new("配布番号"),
+                                TextFont {
+                                    font: game_fonts.dot_gothic.clone(),
+                                    font_size: 12.,
+                                    ..Default::default()
+                                },
+                                TextColor(Color::srgb_u8(128, 128, 128)),
+                            ));
+                            parent
+                                .spawn((
+                                    Node {
+                                        width: Val::Px(130.),
+                                        height: Val::Px(22.),
+                                        border: UiRect::all(Val::Px(1.)),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..Default::default()
+                                    },
+                                    BorderColor(Color::srgb_u8(128, 128, 128)),
+                                    BackgroundColor(Color::WHITE),
+                                ))
+                                .with_child((
+                                    Text::new("ランダム"),
+                                    TextFont {
+                                        font: game_fonts.dot_gothic.clone(),
+                                        font_size: 12.,
+                                        ..Default::default()
+                                    },
+                                    TextColor(Color::srgb_u8(128, 128, 128)),
+                                    SeedInput(String::new()),
+                                ));
+                        });
                 });
         });
 }
@@ -184,26 +385,325 @@ fn on_click_close(
     commands.trigger(CancelNewGameEvent);
 }
 
-fn on_click_difficulty(
-    click: Trigger<Pointer<Click>>,
+fn on_press_stats(
+    _trigger: Trigger<PressButtonEvent>,
     mut commands: Commands,
-    query: Query<&DifficultyButton>,
 ) {
-    if click.button != PointerButton::Primary {
+    commands.trigger(ShowStatsEvent);
+}
+
+/// 配布番号の入力処理
+fn seed_input_system(
+    mut events: EventReader<KeyboardInput>,
+    mut query: Query<(&mut SeedInput, &mut Text, &mut TextColor)>,
+) {
+    let Ok((mut seed_input, mut text, mut color)) = query.get_single_mut() else {
         return;
-    }
-    
-    if let Ok(button) = query.get(click.entity()) {
-        match button.0 {
-            GameDifficulty::Easy => {
-                commands.remove_resource::<GameDifficulty>();
-                commands.insert_resource(GameDifficulty::Easy);
+    };
+
+    let mut changed = false;
+    for event in events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Character(character) => {
+                for c in character.chars().filter(|c| c.is_ascii_digit()) {
+                    if seed_input.0.len() < MAX_SEED_DIGITS {
+                        seed_input.0.push(c);
+                        changed = true;
+                    }
+                }
             }
-            GameDifficulty::Hard => {
-                commands.remove_resource::<GameDifficulty>();
-                commands.insert_resource(GameDifficulty::Hard);
+            Key::Backspace => {
+                changed |= seed_input.0.pop().is_some();
             }
+            _ => {}
+        }
+    }
+    if !changed {
+        return;
+    }
+
+    if seed_input.0.is_empty() {
+        text.0 = String::from("ランダム");
+        color.0 = Color::srgb_u8(128, 128, 128);
+    } else {
+        text.0 = seed_input.0.clone();
+        color.0 = Color::BLACK;
+    }
+}
+
+/// 「勝てる配布のみ」の切り替え
+fn on_press_winnable_only(
+    _trigger: Trigger<PressButtonEvent>,
+    mut options: ResMut<GameOptions>,
+    mut query: Query<&mut Text, With<WinnableOnlyText>>,
+) {
+    options.winnable_only = !options.winnable_only;
+    savedata::save_options(&options);
+    if let Ok(mut text) = query.get_single_mut() {
+        text.0 = String::from(get_winnable_only_label(options.winnable_only));
+    }
+}
+
+/// ゲームの設定のボタンを追加
+fn spawn_option_buttons(
+    parent: &mut ChildBuilder,
+    game_fonts: &GameFonts,
+    options: &GameOptions,
+    option: GameOption,
+) {
+    match option {
+        GameOption::CustomDraw => {
+            parent
+                .spawn((Button, DifficultyButton(GameDifficulty::Custom)))
+                .with_child((
+                    Text::new("カスタム"),
+                    TextFont {
+                        font: game_fonts.dot_gothic.clone(),
+                        font_size: 12.,
+                        ..Default::default()
+                    },
+                    TextColor(Color::BLACK),
+                ))
+                .observe(on_press_difficulty);
+            spawn_option_button(parent, game_fonts, get_custom_turn_label(options.custom_draw), CustomTurnText)
+                .observe(on_press_custom_turn);
+            spawn_option_button(parent, game_fonts, get_custom_fan_label(options.custom_draw), CustomFanText)
+                .observe(on_press_custom_fan);
+        }
+        GameOption::WinnableOnly => {
+            spawn_option_button(parent, game_fonts, get_winnable_only_label(options.winnable_only), WinnableOnlyText)
+                .observe(on_press_winnable_only);
         }
+        GameOption::Vegas => {
+            spawn_option_button(parent, game_fonts, get_vegas_label(options.scoring), VegasText)
+                .observe(on_press_vegas);
+        }
+        GameOption::StockPasses => {
+            spawn_option_button(parent, game_fonts, get_stock_passes_label(options.stock_passes), StockPassesText)
+                .observe(on_press_stock_passes);
+        }
+        GameOption::SpiderSuits => {
+            spawn_option_button(parent, game_fonts, get_spider_suits_label(options.spider_suits), SpiderSuitsText)
+                .observe(on_press_spider_suits);
+        }
+        GameOption::WrapRanks => {
+            spawn_option_button(parent, game_fonts, get_wrap_ranks_label(options.wrap_ranks), WrapRanksText)
+                .observe(on_press_wrap_ranks);
+        }
+    }
+}
+
+/// 設定を切り替えるボタンを追加（ラベルの文字に marker を付ける）
+fn spawn_option_button<'a>(
+    parent: &'a mut ChildBuilder,
+    game_fonts: &GameFonts,
+    label: impl Into<String>,
+    marker: impl Component,
+) -> EntityCommands<'a> {
+    let mut button = parent.spawn(Button);
+    button.with_child((
+        Text::new(label),
+        TextFont {
+            font: game_fonts.dot_gothic.clone(),
+            font_size: 12.,
+            ..Default::default()
+        },
+        TextColor(Color::srgb_u8(128, 128, 128)),
+        marker,
+    ));
+    button
+}
+
+fn get_winnable_only_label(winnable_only: bool) -> &'static str {
+    if winnable_only {
+        "■ 勝てる配布のみ"
+    } else {
+        "□ 勝てる配布のみ"
+    }
+}
+
+/// 「ベガス方式」の切り替え
+fn on_press_vegas(
+    _trigger: Trigger<PressButtonEvent>,
+    mut options: ResMut<GameOptions>,
+    mut query: Query<&mut Text, With<VegasText>>,
+) {
+    options.scoring = match options.scoring {
+        ScoringMode::Standard => ScoringMode::Vegas,
+        ScoringMode::Vegas => ScoringMode::Standard,
+    };
+    savedata::save_options(&options);
+    if let Ok(mut text) = query.get_single_mut() {
+        text.0 = String::from(get_vegas_label(options.scoring));
+    }
+}
+
+fn get_vegas_label(scoring: ScoringMode) -> &'static str {
+    match scoring {
+        ScoringMode::Standard => "□ ベガス方式",
+        ScoringMode::Vegas => "■ ベガス方式",
+    }
+}
+
+/// 山札を戻せる回数の切り替え
+fn on_press_stock_passes(
+    _trigger: Trigger<PressButtonEvent>,
+    mut options: ResMut<GameOptions>,
+    mut query: Query<&mut Text, With<StockPassesText>>,
+) {
+    options.stock_passes = match options.stock_passes {
+        StockPasses::Unlimited => StockPasses::Three,
+        StockPasses::Three => StockPasses::One,
+        StockPasses::One => StockPasses::Unlimited,
+    };
+    savedata::save_options(&options);
+    if let Ok(mut text) = query.get_single_mut() {
+        text.0 = String::from(get_stock_passes_label(options.stock_passes));
+    }
+}
+
+fn get_stock_passes_label(stock_passes: StockPasses) -> &'static str {
+    match stock_passes {
+        StockPasses::Unlimited => "山札のめくり直し: 無制限",
+        StockPasses::Three => "山札のめくり直し: 3周まで",
+        StockPasses::One => "山札のめくり直し: 1周まで",
+    }
+}
+
+/// カスタムでめくる枚数の切り替え
+fn on_press_custom_turn(
+    _trigger: Trigger<PressButtonEvent>,
+    mut options: ResMut<GameOptions>,
+    mut query: Query<&mut Text, With<CustomTurnText>>,
+) {
+    options.custom_draw.num_turn = options.custom_draw.num_turn % MAX_DRAW_COUNT + 1;
+    savedata::save_options(&options);
+    if let Ok(mut text) = query.get_single_mut() {
+        text.0 = get_custom_turn_label(options.custom_draw);
+    }
+}
+
+fn get_custom_turn_label(draw: DrawRule) -> String {
+    format!("めくる: {}枚", draw.num_turn)
+}
+
+/// カスタムでずらして見せる枚数の切り替え
+fn on_press_custom_fan(
+    _trigger: Trigger<PressButtonEvent>,
+    mut options: ResMut<GameOptions>,
+    mut query: Query<&mut Text, With<CustomFanText>>,
+) {
+    options.custom_draw.num_fan = options.custom_draw.num_fan % MAX_DRAW_COUNT + 1;
+    savedata::save_options(&options);
+    if let Ok(mut text) = query.get_single_mut() {
+        text.0 = get_custom_fan_label(options.custom_draw);
+    }
+}
+
+fn get_custom_fan_label(draw: DrawRule) -> String {
+    format!("表示: {}枚", draw.num_fan)
+}
+
+/// ゲーム選択に戻る
+fn on_press_change_game(
+    _trigger: Trigger<PressButtonEvent>,
+    mut commands: Commands,
+) {
+    commands.trigger(ChangeGameEvent);
+}
+
+/// スパイダーのスート数の切り替え
+fn on_press_spider_suits(
+    _trigger: Trigger<PressButtonEvent>,
+    mut options: ResMut<GameOptions>,
+    mut query: Query<&mut Text, With<SpiderSuitsText>>,
+) {
+    options.spider_suits = match options.spider_suits {
+        SpiderSuits::One => SpiderSuits::Two,
+        SpiderSuits::Two => SpiderSuits::Four,
+        SpiderSuits::Four => SpiderSuits::One,
+    };
+    savedata::save_options(&options);
+    if let Ok(mut text) = query.get_single_mut() {
+        text.0 = String::from(get_spider_suits_label(options.spider_suits));
+    }
+}
+
+fn get_spider_suits_label(spider_suits: SpiderSuits) -> &'static str {
+    match spider_suits {
+        SpiderSuits::One => "スート数: 1",
+        SpiderSuits::Two => "スート数: 2",
+        SpiderSuits::Four => "スート数: 4",
+    }
+}
+
+/// 「K と A をつなぐ」の切り替え（トライピークス、ゴルフ）
+fn on_press_wrap_ranks(
+    _trigger: Trigger<PressButtonEvent>,
+    mut options: ResMut<GameOptions>,
+    mut query: Query<&mut Text, With<WrapRanksText>>,
+) {
+    options.wrap_ranks = !options.wrap_ranks;
+    savedata::save_options(&options);
+    if let Ok(mut text) = query.get_single_mut() {
+        text.0 = String::from(get_wrap_ranks_label(options.wrap_ranks));
+    }
+}
+
+fn get_wrap_ranks_label(wrap_ranks: bool) -> &'static str {
+    if wrap_ranks {
+        "■ K と A をつなぐ"
+    } else {
+        "□ K と A をつなぐ"
+    }
+}
+
+fn on_press_replay(
+    _trigger: Trigger<PressButtonEvent>,
+    mut commands: Commands,
+    mut deal_seed: ResMut<DealSeed>,
+) {
+    deal_seed.fixed = true;
+    commands.trigger(DifficultySelectEvent);
+}
+
+fn on_press_difficulty(
+    press: Trigger<PressButtonEvent>,
+    mut commands: Commands,
+    mut deal_seed: ResMut<DealSeed>,
+    options: Res<GameOptions>,
+    query: Query<&DifficultyButton>,
+    query_seed_input: Query<&SeedInput>,
+) {
+    // 配布番号が入力されていればその配布で遊ぶ
+    if let Ok(seed_input) = query_seed_input.get_single() {
+        if let Ok(seed) = seed_input.0.parse::<u64>() {
+            deal_seed.seed = seed;
+            deal_seed.fixed = true;
+        }
+    }
+    
+    if let Ok(button) = query.get(press.entity()) {
+        commands.insert_resource(button.0);
+        commands.insert_resource(DrawRule::new(button.0, &options));
         commands.trigger(DifficultySelectEvent);
     }
 }
+
+fn on_press_continue(
+    _trigger: Trigger<PressButtonEvent>,
+    mut commands: Commands,
+) {
+    if let Some(saved_game) = savedata::load_game() {
+        if saved_game.cards.len() != saved_game.options.game.ruleset().num_cards() {
+            return;
+        }
+        commands.remove_resource::<GameDifficulty>();
+        commands.insert_resource(saved_game.difficulty);
+        commands.insert_resource(saved_game);
+        commands.trigger(ContinueEvent);
+    }
+}
@@ -0,0 +1,329 @@
+use bevy::{
+    app::{App, Plugin, Update},
+    prelude::*,
+    ui::widget::NodeImageMode,
+};
+use crate::{
+    game::{GameSelectEvent, PressButtonEvent},
+    menuinput::{Menu, MenuFocus},
+    resources::{ClickMode, GameFonts, GameKind, GameOptions, GameTextures},
+    savedata,
+    stats::{self, Stats},
+    GameState,
+};
+
+#[derive(Component)]
+struct GameButton(GameKind);
+
+#[derive(Component)]
+struct GameLabel(GameKind);
+
+/// 選んだゲームの説明欄
+#[derive(Component)]
+enum InfoText {
+    Name,
+    Summary,
+    BestStats,
+}
+
+#[derive(Component)]
+struct ClickModeText;
+
+#[derive(Component)]
+struct UISelectGame;
+
+pub struct SelectGamePlugin;
+
+impl Plugin for SelectGamePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(GameState::SelectGame), spawn_ui)
+            .add_systems(OnExit(GameState::SelectGame), despawn_ui)
+            .add_systems(
+                Update,
+                update_ui_system.run_if(
+                    in_state(GameState::SelectGame)
+                    .and(resource_changed::<GameOptions>)
+                )
+            );
+    }
+}
+
+fn spawn_ui(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    game_fonts: Res<GameFonts>,
+    options: Res<GameOptions>,
+    stats: Res<Stats>,
+) {
+    let game = options.game;
+    let ruleset = game.ruleset();
+    let text_font = TextFont {
+        font: game_fonts.dot_gothic.clone(),
+        font_size: 12.,
+        ..Default::default()
+    };
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Px(568.),
+                height: Val::Px(320.),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            ZIndex(1),
+            BackgroundColor(Color::srgba_u8(0, 0, 0, 230)),
+            UISelectGame,
+            Menu,
+        ))
+        .with_children(|parent| {
+            let slicer = TextureSlicer {
+                border: BorderRect::square(7.),
+                center_scale_mode: SliceScaleMode::Stretch,
+                sides_scale_mode: SliceScaleMode::Stretch,
+                max_corner_scale: 1.,
+            };
+            parent
+                .spawn((
+                    ImageNode {
+                        image: game_textures.window.clone(),
+                        image_mode: NodeImageMode::Sliced(slicer),
+                        ..default()
+                    },
+                    Node {
+                        width: Val::Px(420.),
+                        height: Val::Px(260.),
+                        justify_content: JustifyContent::Center,
+                        ..Default::default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new("ゲームを選択"),
+                        TextFont {
+                            font: game_fonts.dot_gothic.clone(),
+                            font_size: 16.,
+                            ..Default::default()
+                        },
+                        TextColor(Color::srgb_u8(128, 128, 128)),
+                        Node {
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(20.),
+                            ..Default::default()
+                        },
+                    ));
+
+                    // ゲームの一覧
+                    parent
+                        .spawn(Node {
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(52.),
+                            left: Val::Px(20.),
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(6.),
+                            ..Default::default()
+                        })
+                        .with_children(|parent| {
+                            for kind in GameKind::ALL {
+                                parent
+                                    .spawn((Button, GameButton(kind)))
+                                    .with_child((
+                                        Text::new(get_game_label(kind, game)),
+                                        text_font.clone(),
+                                        TextColor(get_game_color(kind, game)),
+                                        GameLabel(kind),
+                                    ))
+                                    .observe(on_press_game);
+                            }
+                        });
+
+                    // 選んだゲームのルールと成績
+                    parent
+                        .spawn(Node {
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(52.),
+                            left: Val::Px(150.),
+                            width: Val::Px(250.),
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(8.),
+                            ..Default::default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                Text::new(ruleset.name()),
+                                TextFont {
+                                    font: game_fonts.dot_gothic.clone(),
+                                    font_size: 16.,
+                                    ..Default::default()
+                                },
+                                TextColor(Color::BLACK),
+                                InfoText::Name,
+                            ));
+                            parent.spawn((
+                                Text::new(ruleset.summary()),
+                                text_font.clone(),
+                                TextColor(Color::BLACK),
+                                InfoText::Summary,
+                            ));
+                            parent.spawn((
+                                Text::new(get_best_stats_label(&stats, game)),
+                                text_font.clone(),
+                                TextColor(Color::srgb_u8(128, 128, 128)),
+                                InfoText::BestStats,
+                            ));
+                        });
+
+                    // どのゲームでも使う設定
+                    parent
+                        .spawn((
+                            Button,
+                            Node {
+                                position_type: PositionType::Absolute,
+                                bottom: Val::Px(28.),
+                                left: Val::Px(150.),
+                                ..Default::default()
+                            },
+                        ))
+                        .with_child((
+                            Text::new(get_click_mode_label(options.click_mode)),
+                            text_font.clone(),
+                            TextColor(Color::srgb_u8(128, 128, 128)),
+                            ClickModeText,
+                        ))
+                        .observe(on_press_click_mode);
+
+                    parent
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(100.),
+                                height: Val::Px(40.),
+                                position_type: PositionType::Absolute,
+                                bottom: Val::Px(16.),
+                                right: Val::Px(20.),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                            MenuFocus,
+                        ))
+                        .with_child((
+                            Text::new("次へ"),
+                            TextFont {
+                                font: game_fonts.dot_gothic.clone(),
+                                font_size: 16.,
+                                ..Default::default()
+                            },
+                            TextColor(Color::BLACK),
+                        ))
+                        .observe(on_press_next);
+                });
+        });
+}
+
+fn despawn_ui(
+    mut commands: Commands,
+    query: Query<Entity, With<UISelectGame>>,
+) {
+    if let Ok(entity) = query.get_single() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// 選んだゲームに合わせた表示の更新
+fn update_ui_system(
+    options: Res<GameOptions>,
+    stats: Res<Stats>,
+    mut query_label: Query<(&GameLabel, &mut Text, &mut TextColor)>,
+    mut query_info: Query<(&InfoText, &mut Text), Without<GameLabel>>,
+) {
+    let game = options.game;
+    let ruleset = game.ruleset();
+    for (label, mut text, mut color) in query_label.iter_mut() {
+        text.0 = get_game_label(label.0, game);
+        color.0 = get_game_color(label.0, game);
+    }
+    for (info, mut text) in query_info.iter_mut() {
+        text.0 = match info {
+            InfoText::Name => String::from(ruleset.name()),
+            InfoText::Summary => String::from(ruleset.summary()),
+            InfoText::BestStats => get_best_stats_label(&stats, game),
+        };
+    }
+}
+
+/// ゲームの選択
+fn on_press_game(
+    press: Trigger<PressButtonEvent>,
+    mut options: ResMut<GameOptions>,
+    query: Query<&GameButton>,
+) {
+    // 選んだゲームは次に起動したときも選ばれた状態にする
+    let Ok(button) = query.get(press.entity()) else {
+        return;
+    };
+    if options.game != button.0 {
+        options.game = button.0;
+        savedata::save_options(&options);
+    }
+}
+
+/// 「選んでから置く」の切り替え
+fn on_press_click_mode(
+    _trigger: Trigger<PressButtonEvent>,
+    mut options: ResMut<GameOptions>,
+    mut query: Query<&mut Text, With<ClickModeText>>,
+) {
+    options.click_mode = match options.click_mode {
+        ClickMode::AutoMove => ClickMode::SelectThenPlace,
+        ClickMode::SelectThenPlace => ClickMode::AutoMove,
+    };
+    savedata::save_options(&options);
+    if let Ok(mut text) = query.get_single_mut() {
+        text.0 = String::from(get_click_mode_label(options.click_mode));
+    }
+}
+
+fn get_click_mode_label(click_mode: ClickMode) -> &'static str {
+    match click_mode {
+        ClickMode::AutoMove => "□ 選んでから置く",
+        ClickMode::SelectThenPlace => "■ 選んでから置く",
+    }
+}
+
+fn on_press_next(
+    _trigger: Trigger<PressButtonEvent>,
+    mut commands: Commands,
+) {
+    commands.trigger(GameSelectEvent);
+}
+
+fn get_game_label(kind: GameKind, selected: GameKind) -> String {
+    let mark = if kind == selected { "▶" } else { "　" };
+    format!("{} {}", mark, kind.ruleset().name())
+}
+
+fn get_game_color(kind: GameKind, selected: GameKind) -> Color {
+    if kind == selected {
+        Color::BLACK
+    } else {
+        Color::srgb_u8(128, 128, 128)
+    }
+}
+
+/// 最高記録の表記を取得（難易度はまとめる）
+fn get_best_stats_label(stats: &Stats, game: GameKind) -> String {
+    let total = stats.get_total(game);
+    if total.played == 0 {
+        return String::from("まだ遊んでいません");
+    }
+    format!(
+        "プレイ回数 {}  勝率 {}%\n最高スコア {}  最短時間 {}",
+        total.played,
+        total.win_rate(),
+        total.best_score.map_or_else(|| String::from("-"), |score| score.to_string()),
+        total.best_time.map_or_else(|| String::from("-"), stats::format_time),
+    )
+}
@@ -0,0 +1,327 @@
+use std::{
+    collections::HashSet,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    prelude::*,
+    tasks::{block_on, poll_once, AsyncComputeTaskPool, Task},
+};
+use rand::Rng;
+use crate::{
+    board::{foundation_suit, AppliedMove, Board, BoardCard, Move, NUM_PILES, NUM_SUIT},
+    cardlist::CardList,
+    game::{ContinueEvent, DealEvent, MAX_RANDOM_SEED},
+    resources::{DrawRule, GameKind, StockRecycle},
+    savedata::SavedGame,
+};
+
+const SOLVE_BUDGET: usize = 500_000;
+const SEARCH_BUDGET: usize = 100_000;
+const MAX_SEARCH_DEALS: usize = 50;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SolveResult {
+    /// 勝てる（見つけた手順の手数。最短とは限らない）
+    Winnable(u32),
+    Unwinnable,
+    Unknown,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum AnalysisStatus {
+    #[default]
+    None,
+    Searching,
+    Solving,
+    Done(SolveResult),
+}
+
+#[derive(Default, Resource)]
+pub struct DealAnalysis {
+    pub status: AnalysisStatus,
+    task: Option<Task<SolveResult>>,
+}
+
+impl DealAnalysis {
+    /// 判定をやめる
+    pub fn reset(&mut self) {
+        self.task = None;
+        self.status = AnalysisStatus::None;
+    }
+
+    /// 勝てる配布の探索中にする
+    pub fn set_searching(&mut self) {
+        self.task = None;
+        self.status = AnalysisStatus::Searching;
+    }
+}
+
+pub struct SolverPlugin;
+
+impl Plugin for SolverPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, init_system)
+            .add_systems(Update, poll_system);
+    }
+}
+
+fn init_system(
+    mut commands: Commands,
+) {
+    commands.insert_resource(DealAnalysis::default());
+    commands.add_observer(on_deal);
+    commands.add_observer(on_continue);
+}
+
+/// 判定結果の受け取り
+fn poll_system(
+    mut analysis: ResMut<DealAnalysis>,
+) {
+    let Some(task) = analysis.task.as_mut() else {
+        return;
+    };
+    if let Some(result) = block_on(poll_once(task)) {
+        analysis.task = None;
+        analysis.status = AnalysisStatus::Done(result);
+    }
+}
+
+fn on_deal(
+    _trigger: Trigger<DealEvent>,
+    mut analysis: ResMut<DealAnalysis>,
+    card_list: Res<CardList>,
+    draw: Res<DrawRule>,
+    game: Res<GameKind>,
+    stock_recycle: Res<StockRecycle>,
+) {
+    // 判定できるのはクロンダイクのみ
+    if *game != GameKind::Klondike {
+        analysis.reset();
+        return;
+    }
+
+    let board = card_list.to_board(NUM_PILES, NUM_SUIT, draw.num_turn, &stock_recycle);
+    start_analysis(&mut analysis, board);
+}
+
+fn on_continue(
+    _trigger: Trigger<ContinueEvent>,
+    mut analysis: ResMut<DealAnalysis>,
+    card_list: Res<CardList>,
+    draw: Res<DrawRule>,
+    saved_game: Res<SavedGame>,
+    stock_recycle: Res<StockRecycle>,
+) {
+    if saved_game.options.game != GameKind::Klondike {
+        analysis.reset();
+        return;
+    }
+
+    let board = card_list.to_board(NUM_PILES, NUM_SUIT, draw.num_turn, &stock_recycle);
+    start_analysis(&mut analysis, board);
+}
+
+/// バックグラウンドで盤面の判定を開始
+fn start_analysis(analysis: &mut DealAnalysis, board: Board) {
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        solve(board, SOLVE_BUDGET)
+    });
+    analysis.task = Some(task);
+    analysis.status = AnalysisStatus::Solving;
+}
+
+/// 勝てる配布の配布番号を探す
+pub fn find_winnable_seed(num_turn_to_waste: u32, max_recycle: Option<u32>) -> Option<u64> {
+    let mut rng = rand::thread_rng();
+    (0..MAX_SEARCH_DEALS).map(|_| {
+        rng.gen_range(0..MAX_RANDOM_SEED)
+    }).find(|seed| {
+        let mut board = Board::deal(*seed, num_turn_to_waste);
+        board.max_recycle = max_recycle;
+        matches!(solve(board, SEARCH_BUDGET), SolveResult::Winnable(_))
+    })
+}
+
+/// 深さ優先探索で盤面を解く（budget は調べる盤面の上限）
+pub fn solve(mut board: Board, budget: usize) -> SolveResult {
+    struct Frame {
+        moves: Vec<Move>,
+        next: usize,
+        applied: Option<AppliedMove>,
+    }
+
+    if board.is_won() {
+        return SolveResult::Winnable(0);
+    }
+
+    let mut visited = HashSet::new();
+    visited.insert(hash_board(&board));
+    let mut stack = vec![Frame {
+        moves: get_candidate_moves(&board),
+        next: 0,
+        applied: None,
+    }];
+
+    while let Some(frame) = stack.last_mut() {
+        let Some(mv) = frame.moves.get(frame.next).copied() else {
+            if let Some(applied) = frame.applied {
+                board.revert(&applied);
+            }
+            stack.pop();
+            continue;
+        };
+        frame.next += 1;
+
+        let Some(applied) = board.apply(mv) else {
+            continue;
+        };
+        if board.is_won() {
+            return SolveResult::Winnable(stack.len() as u32);
+        }
+
+        // 一度調べた盤面は飛ばす
+        if !visited.insert(hash_board(&board)) {
+            board.revert(&applied);
+            continue;
+        }
+        if visited.len() > budget {
+            return SolveResult::Unknown;
+        }
+
+        stack.push(Frame {
+            moves: get_candidate_moves(&board),
+            next: 0,
+            applied: Some(applied),
+        });
+    }
+
+    SolveResult::Unwinnable
+}
+
+/// 調べる移動を有望な順に取得
+fn get_candidate_moves(board: &Board) -> Vec<Move> {
+    let moves = board.legal_moves();
+
+    // 組札に移しても困らないカードがあれば、その移動だけを調べる
+    if let Some(mv) = moves.iter().find(|mv| {
+        get_foundation_card(board, **mv).is_some_and(|card| is_safe_foundation(board, card))
+    }) {
+        return vec![*mv];
+    }
+
+    let mut moves: Vec<(u32, Move)> = moves.into_iter().filter_map(|mv| {
+        get_priority(board, mv).map(|priority| (priority, mv))
+    }).collect();
+    moves.sort_by_key(|(priority, _)| *priority);
+    moves.into_iter().map(|(_, mv)| mv).collect()
+}
+
+/// 移動の優先度を取得（小さいほど先に調べる。意味のない移動は None）
+fn get_priority(board: &Board, mv: Move) -> Option<u32> {
+    match mv {
+        Move::WasteToFoundation(_) | Move::PileToFoundation { .. } => Some(0),
+        Move::PileToPile { src, index, dst } => {
+            // 列をまるごと空いた列に移しても意味がない
+            if index == 0 {
+                return (!board.piles[dst].is_empty()).then_some(3);
+            }
+            if board.piles[src][index - 1].facedown {
+                Some(1)
+            } else {
+                Some(5)
+            }
+        }
+        Move::WasteToPile(_) => Some(2),
+        Move::StockToWaste => Some(4),
+        Move::WasteToStock => Some(6),
+        Move::FoundationToPile { .. } => Some(7),
+    }
+}
+
+/// 組札に移動するカードを取得
+fn get_foundation_card(board: &Board, mv: Move) -> Option<&BoardCard> {
+    match mv {
+        Move::WasteToFoundation(_) => board.waste.last(),
+        Move::PileToFoundation { src, .. } => board.piles[src].last(),
+        _ => None,
+    }
+}
+
+/// 組札に移しても困らないかどうか（反対の色の1つ小さいカードがすでに組札にある）
+fn is_safe_foundation(board: &Board, card: &BoardCard) -> bool {
+    card.number <= 2 || board.foundations.iter().enumerate().filter(|(slot, _)| {
+        foundation_suit(*slot).is_red() != card.suit.is_red()
+    }).all(|(_, foundation)| {
+        foundation.len() as u32 + 1 >= card.number
+    })
+}
+
+/// 盤面のハッシュ値を取得
+fn hash_board(board: &Board) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    board.stock.hash(&mut hasher);
+    board.waste.hash(&mut hasher);
+    board.piles.hash(&mut hasher);
+    board.foundations.hash(&mut hasher);
+    // 山札を何度でも戻せるなら、戻した回数が違うだけの盤面は同じ盤面
+    if board.max_recycle.is_some() {
+        board.num_recycle.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::CardSuit;
+
+    // 解けることを確かめた配布番号
+    const WINNABLE_SEED: u64 = 2;
+
+    fn card(id: usize, suit: CardSuit, number: u32) -> BoardCard {
+        BoardCard {
+            id,
+            suit,
+            number,
+            facedown: false,
+        }
+    }
+
+    #[test]
+    fn solve_finishes_nearly_won_board() {
+        let mut board = Board::new(1);
+        board.piles[0].push(card(0, CardSuit::Spade, 2));
+        board.piles[1].push(card(1, CardSuit::Spade, 1));
+        assert_eq!(solve(board, SEARCH_BUDGET), SolveResult::Winnable(2));
+
+        assert_eq!(solve(Board::new(1), SEARCH_BUDGET), SolveResult::Winnable(0));
+    }
+
+    #[test]
+    fn solve_detects_blocked_board() {
+        // A の上に 2 が重なり、どこにも動かせない
+        let mut board = Board::new(1);
+        board.piles[0].push(card(0, CardSuit::Spade, 1));
+        board.piles[0].push(card(1, CardSuit::Spade, 2));
+        assert_eq!(solve(board, SEARCH_BUDGET), SolveResult::Unwinnable);
+    }
+
+    #[test]
+    fn solve_detects_blocked_board_with_unlimited_passes() {
+        // 山札を何度戻しても、めくったカードはどこにも置けない
+        let mut board = Board::new(1);
+        board.piles[0].push(card(0, CardSuit::Spade, 1));
+        board.piles[0].push(card(1, CardSuit::Spade, 2));
+        board.stock.push(card(2, CardSuit::Heart, 5));
+        board.max_recycle = None;
+        assert_eq!(solve(board, SEARCH_BUDGET), SolveResult::Unwinnable);
+    }
+
+    #[test]
+    fn solve_wins_fixed_seed() {
+        let board = Board::deal(WINNABLE_SEED, 1);
+        assert!(matches!(solve(board, SOLVE_BUDGET), SolveResult::Winnable(_)));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    board::{shuffle_deck, CardSuit},
+    resources::SpiderSuits,
+};
+
+pub const NUM_SPIDER_DECKS: usize = 2;
+pub const NUM_SPIDER_PILES: usize = 10;
+pub const NUM_SPIDER_DEAL: usize = 54;
+pub const NUM_RUN_CARDS: usize = 13;
+
+/// 配布番号からシャッフルした2組分のカードの並びを取得
+pub fn shuffled_deck(seed: u64, spider_suits: SpiderSuits) -> Vec<(CardSuit, u32)> {
+    // スートが少ないときは同じスートを繰り返して枚数を合わせる
+    let suits = spider_suits.suits();
+    let mut deck = Vec::new();
+    for index in 0..NUM_SPIDER_DECKS * CardSuit::ALL.len() {
+        for number in 1..=13 {
+            deck.push((suits[index % suits.len()], number));
+        }
+    }
+
+    shuffle_deck(&mut deck, seed);
+    deck
+}
+
+/// 場札に重ねられるかどうかを判定（スートは問わない）
+pub fn can_stack_pile(top: Option<u32>, number: u32) -> bool {
+    match top {
+        Some(top_number) => number + 1 == top_number,
+        None => true,
+    }
+}
+
+/// 同じスートで数字が1つずつ小さくなっているかどうかを判定
+pub fn is_run(cards: &[(CardSuit, u32)]) -> bool {
+    cards.windows(2).all(|pair| {
+        let ((suit, number), (next_suit, next_number)) = (pair[0], pair[1]);
+        suit == next_suit && number == next_number + 1
+    })
+}
+
+/// K から A まで揃った列かどうかを判定
+pub fn is_complete_run(cards: &[(CardSuit, u32)]) -> bool {
+    cards.len() == NUM_RUN_CARDS
+        && cards.first().is_some_and(|(_, number)| *number == 13)
+        && is_run(cards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(suit: CardSuit, from: u32, to: u32) -> Vec<(CardSuit, u32)> {
+        (to..=from).rev().map(|number| (suit, number)).collect()
+    }
+
+    #[test]
+    fn complete_run_is_king_to_ace_in_one_suit() {
+        assert!(is_complete_run(&run(CardSuit::Spade, 13, 1)));
+        assert!(!is_complete_run(&run(CardSuit::Spade, 13, 2)));
+        assert!(!is_complete_run(&run(CardSuit::Spade, 12, 1)));
+
+        let mut mixed = run(CardSuit::Spade, 13, 1);
+        mixed[6].0 = CardSuit::Heart;
+        assert!(!is_complete_run(&mixed));
+
+        let mut gap = run(CardSuit::Spade, 13, 1);
+        gap[6].1 = 5;
+        assert!(!is_complete_run(&gap));
+    }
+
+    #[test]
+    fn any_suit_stacks_one_lower() {
+        assert!(can_stack_pile(None, 7));
+        assert!(can_stack_pile(Some(8), 7));
+        assert!(!can_stack_pile(Some(9), 7));
+    }
+
+    #[test]
+    fn deck_uses_only_selected_suits() {
+        for spider_suits in [SpiderSuits::One, SpiderSuits::Two, SpiderSuits::Four] {
+            let deck = shuffled_deck(1, spider_suits);
+            assert_eq!(deck.len(), NUM_SPIDER_DECKS * CardSuit::ALL.len() * 13);
+            for suit in CardSuit::ALL {
+                let count = deck.iter().filter(|(card_suit, _)| *card_suit == suit).count();
+                if spider_suits.suits().contains(&suit) {
+                    assert_eq!(count, deck.len() / spider_suits.suits().len());
+                } else {
+                    assert_eq!(count, 0);
+                }
+            }
+        }
+    }
+}
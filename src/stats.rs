@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    prelude::*,
+    ui::widget::NodeImageMode,
+};
+use serde::{Deserialize, Serialize};
+use crate::{
+    game::{CancelNewGameEvent, ContinueEvent, DifficultySelectEvent, GameClearEvent, GameOverEvent, NewGameEvent, ShowStatsEvent},
+    information::{self, Movement, Score},
+    resources::{DrawRule, GameDifficulty, GameFonts, GameKind, GameOptions, GameTextures},
+    ruleset::GameOption,
+    savedata,
+};
+
+const SAVE_STATS: &str = "stats.json";
+
+#[derive(Clone, Copy, Default, Deserialize, Serialize)]
+pub struct DifficultyStats {
+    pub played: u32,
+    pub won: u32,
+    pub current_streak: u32,
+    pub best_streak: u32,
+    pub best_time: Option<f32>,
+    pub fewest_moves: Option<u32>,
+    pub best_score: Option<i32>,
+}
+
+impl DifficultyStats {
+    /// 勝率（%）を取得
+    pub fn win_rate(&self) -> u32 {
+        (self.won * 100).checked_div(self.played).unwrap_or(0)
+    }
+
+    /// 勝ちを記録
+    fn record_win(&mut self, time: f32, movement: u32, score: i32) {
+        self.won += 1;
+        self.current_streak += 1;
+        self.best_streak = self.best_streak.max(self.current_streak);
+        self.best_time = Some(self.best_time.map_or(time, |best| best.min(time)));
+        self.fewest_moves = Some(self.fewest_moves.map_or(movement, |best| best.min(movement)));
+        self.best_score = Some(self.best_score.map_or(score, |best| best.max(score)));
+    }
+
+    /// 負けを記録
+    fn record_loss(&mut self) {
+        self.current_streak = 0;
+    }
+
+    /// 別の難易度の成績と合わせる（連勝は長いほう、記録は良いほう）
+    fn merge(&self, other: &DifficultyStats) -> DifficultyStats {
+        fn best<T: Copy>(a: Option<T>, b: Option<T>, pick: fn(T, T) -> T) -> Option<T> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(pick(a, b)),
+                (a, b) => a.or(b),
+            }
+        }
+        DifficultyStats {
+            played: self.played + other.played,
+            won: self.won + other.won,
+            current_streak: self.current_streak.max(other.current_streak),
+            best_streak: self.best_streak.max(other.best_streak),
+            best_time: best(self.best_time, other.best_time, f32::min),
+            fewest_moves: best(self.fewest_moves, other.fewest_moves, u32::min),
+            best_score: best(self.best_score, other.best_score, i32::max),
+        }
+    }
+}
+
+#[derive(Default, Deserialize, Resource, Serialize)]
+pub struct Stats {
+    games: HashMap<GameKind, HashMap<GameDifficulty, DifficultyStats>>,
+    /// カスタムの成績（めくる枚数ごと）
+    custom_draws: HashMap<GameKind, HashMap<u32, DifficultyStats>>,
+    in_progress: Option<GameDifficulty>,
+    in_progress_game: GameKind,
+    in_progress_draw: u32,
+    /// 「新しいゲーム」で負けにしたゲームと負ける前の連勝（次のゲームを始めるまでは取り消せる）
+    abandoned: Option<AbandonedGame>,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+struct AbandonedGame {
+    game: GameKind,
+    difficulty: GameDifficulty,
+    num_turn: u32,
+    current_streak: u32,
+}
+
+impl Stats {
+    /// ゲームと難易度ごとの成績を取得
+    pub fn get(&self, game: GameKind, difficulty: GameDifficulty) -> DifficultyStats {
+        self.games.get(&game).and_then(|stats| stats.get(&difficulty)).copied().unwrap_or_default()
+    }
+
+    /// カスタムの成績をめくる枚数ごとに取得
+    pub fn get_custom(&self, game: GameKind, num_turn: u32) -> DifficultyStats {
+        self.custom_draws.get(&game).and_then(|stats| stats.get(&num_turn)).copied().unwrap_or_default()
+    }
+
+    /// ゲームごとの成績を難易度をまとめて取得
+    pub fn get_total(&self, game: GameKind) -> DifficultyStats {
+        let total = self.get(game, GameDifficulty::Easy)
+            .merge(&self.get(game, GameDifficulty::Hard))
+            .merge(&self.get(game, GameDifficulty::Custom));
+        self.custom_draws.get(&game).map_or(total, |stats| {
+            stats.values().fold(total, |total, stats| total.merge(stats))
+        })
+    }
+
+    /// 成績の記録先を取得（カスタムはめくる枚数ごとに分ける）
+    fn entry(&mut self, game: GameKind, difficulty: GameDifficulty, num_turn: u32) -> &mut DifficultyStats {
+        if difficulty == GameDifficulty::Custom {
+            return self.custom_draws.entry(game).or_default().entry(num_turn).or_default();
+        }
+        self.games.entry(game).or_default().entry(difficulty).or_default()
+    }
+
+    /// 終わっていないゲームを負けとして記録
+    fn finish_in_progress(&mut self) {
+        if let Some(difficulty) = self.in_progress.take() {
+            self.entry(self.in_progress_game, difficulty, self.in_progress_draw).record_loss();
+        }
+    }
+
+    /// 終わっていないゲームを負けとして記録（取り消せるように負ける前の連勝を覚えておく）
+    fn abandon_in_progress(&mut self) {
+        if let Some(difficulty) = self.in_progress {
+            let (game, num_turn) = (self.in_progress_game, self.in_progress_draw);
+            self.abandoned = Some(AbandonedGame {
+                game,
+                difficulty,
+                num_turn,
+                current_streak: self.entry(game, difficulty, num_turn).current_streak,
+            });
+            self.finish_in_progress();
+        }
+    }
+
+    /// 負けにしたゲームを続ける
+    fn restore_abandoned(&mut self) {
+        if let Some(abandoned) = self.abandoned.take() {
+            self.entry(abandoned.game, abandoned.difficulty, abandoned.num_turn).current_streak = abandoned.current_streak;
+            self.in_progress = Some(abandoned.difficulty);
+            self.in_progress_game = abandoned.game;
+            self.in_progress_draw = abandoned.num_turn;
+        }
+    }
+}
+
+/// 成績表の行（項目名と値の表記）
+type StatsRow = (&'static str, fn(&DifficultyStats) -> String);
+
+#[derive(Component)]
+struct RecordWin;
+
+#[derive(Component)]
+pub struct UIStats;
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, init_system)
+            .add_systems(Update, record_win_system)
+            .add_systems(Update, close_input_system.run_if(any_with_component::<UIStats>));
+    }
+}
+
+fn init_system(
+    mut commands: Commands,
+) {
+    let stats = savedata::load::<Stats>(SAVE_STATS).unwrap_or_default();
+    commands.insert_resource(stats);
+    commands.add_observer(on_new_game);
+    commands.add_observer(on_cancel_new_game);
+    commands.add_observer(on_continue);
+    commands.add_observer(on_start);
+    commands.add_observer(on_game_clear);
+    commands.add_observer(on_game_over);
+    commands.add_observer(on_show_stats);
+}
+
+/// 勝ちの記録
+fn record_win_system(
+    mut commands: Commands,
+    mut stats: ResMut<Stats>,
+    time: Res<information::Time>,
+    score: Res<Score>,
+    movement: Res<Movement>,
+    query: Query<(Entity, Ref<RecordWin>)>,
+) {
+    for (entity, record_win) in query.iter() {
+        // 最後の移動のスコアの反映を待つため、追加されたフレームでは記録しない
+        if record_win.is_added() {
+            continue;
+        }
+        if let Some(difficulty) = stats.in_progress.take() {
+            let (game, num_turn) = (stats.in_progress_game, stats.in_progress_draw);
+            stats.entry(game, difficulty, num_turn).record_win(time.0, movement.0, score.0);
+            savedata::save(SAVE_STATS, &*stats);
+        }
+        commands.entity(entity).despawn();
+    }
+}
+
+/// 「新しいゲーム」選択時処理（終わっていないゲームを負けとする）
+fn on_new_game(
+    _trigger: Trigger<NewGameEvent>,
+    mut stats: ResMut<Stats>,
+) {
+    stats.abandon_in_progress();
+    savedata::save(SAVE_STATS, &*stats);
+}
+
+/// 「新しいゲーム」をやめたときの処理（負けを取り消す）
+fn on_cancel_new_game(
+    _trigger: Trigger<CancelNewGameEvent>,
+    mut stats: ResMut<Stats>,
+) {
+    stats.restore_abandoned();
+    savedata::save(SAVE_STATS, &*stats);
+}
+
+/// 保存したゲームを続けるときの処理（負けにしたゲームなら取り消す）
+fn on_continue(
+    _trigger: Trigger<ContinueEvent>,
+    mut stats: ResMut<Stats>,
+) {
+    stats.restore_abandoned();
+    savedata::save(SAVE_STATS, &*stats);
+}
+
+/// ゲーム開始時処理（負けにしたゲームを確定し、続けずに始めたゲームも負けとする）
+fn on_start(
+    _trigger: Trigger<DifficultySelectEvent>,
+    mut stats: ResMut<Stats>,
+    difficulty: Res<GameDifficulty>,
+    draw: Res<DrawRule>,
+    options: Res<GameOptions>,
+) {
+    stats.abandoned = None;
+    stats.finish_in_progress();
+    stats.entry(options.game, *difficulty, draw.num_turn).played += 1;
+    stats.in_progress = Some(*difficulty);
+    stats.in_progress_game = options.game;
+    stats.in_progress_draw = draw.num_turn;
+    savedata::save(SAVE_STATS, &*stats);
+}
+
+fn on_game_clear(
+    _trigger: Trigger<GameClearEvent>,
+    mut commands: Commands,
+) {
+    commands.spawn(RecordWin);
+}
+
+fn on_game_over(
+    _trigger: Trigger<GameOverEvent>,
+    mut stats: ResMut<Stats>,
+) {
+    stats.finish_in_progress();
+    savedata::save(SAVE_STATS, &*stats);
+}
+
+/// 成績画面の表示
+fn on_show_stats(
+    _trigger: Trigger<ShowStatsEvent>,
+    mut commands: Commands,
+    stats: Res<Stats>,
+    options: Res<GameOptions>,
+    game_textures: Res<GameTextures>,
+    game_fonts: Res<GameFonts>,
+) {
+    let game = options.game;
+    let mut difficulties = vec![
+        (String::from("簡単"), stats.get(game, GameDifficulty::Easy)),
+        (String::from("難しい"), stats.get(game, GameDifficulty::Hard)),
+    ];
+    // カスタムで遊べるゲームのみ（いま選んでいるめくる枚数の成績）
+    if game.ruleset().has_option(GameOption::CustomDraw) {
+        let num_turn = options.custom_draw.num_turn;
+        difficulties.push((format!("カスタム{}", num_turn), stats.get_custom(game, num_turn)));
+    }
+    let rows: [StatsRow; 8] = [
+        ("プレイ回数", |stats| stats.played.to_string()),
+        ("勝利数", |stats| stats.won.to_string()),
+        ("勝率", |stats| format!("{}%", stats.win_rate())),
+        ("連勝", |stats| stats.current_streak.to_string()),
+        ("最多連勝", |stats| stats.best_streak.to_string()),
+        ("最短時間", |stats| stats.best_time.map_or_else(|| String::from("-"), format_time)),
+        ("最少移動回数", |stats| stats.fewest_moves.map_or_else(|| String::from("-"), |moves| moves.to_string())),
+        ("最高スコア", |stats| stats.best_score.map_or_else(|| String::from("-"), |score| score.to_string())),
+    ];
+
+    let text_font = TextFont {
+        font: game_fonts.dot_gothic.clone(),
+        font_size: 12.,
+        ..Default::default()
+    };
+    let slicer = TextureSlicer {
+        border: BorderRect::square(7.),
+        center_scale_mode: SliceScaleMode::Stretch,
+        sides_scale_mode: SliceScaleMode::Stretch,
+        max_corner_scale: 1.,
+    };
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Px(568.),
+                height: Val::Px(320.),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            ZIndex(2),
+            BackgroundColor(Color::srgba_u8(0, 0, 0, 160)),
+            UIStats,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    ImageNode {
+                        image: game_textures.window.clone(),
+                        image_mode: NodeImageMode::Sliced(slicer),
+                        ..default()
+                    },
+                    Node {
+                        width: Val::Px(140. + 70. * difficulties.len() as f32),
+                        height: Val::Px(240.),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::top(Val::Px(20.)),
+                        row_gap: Val::Px(4.),
+                        ..Default::default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            ImageNode {
+                                image: game_textures.close.clone(),
+                                ..Default::default()
+                            },
+                            Node {
+                                position_type: PositionType::Absolute,
+                                top: Val::Px(10.),
+                                right: Val::Px(10.),
+                                ..Default::default()
+                            },
+                        ))
+                        .observe(on_click_close);
+                    parent.spawn((
+                        Text::new(format!("成績（{}）", game.ruleset().name())),
+                        TextFont {
+                            font: game_fonts.dot_gothic.clone(),
+                            font_size: 16.,
+                            ..Default::default()
+                        },
+                        TextColor(Color::srgb_u8(128, 128, 128)),
+                        Node {
+                            margin: UiRect::bottom(Val::Px(8.)),
+                            ..Default::default()
+                        },
+                    ));
+
+                    // 見出し
+                    parent
+                        .spawn(Node::default())
+                        .with_children(|parent| {
+                            spawn_cell(parent, "", &text_font, 100.);
+                            for (name, _) in difficulties.iter() {
+                                spawn_cell(parent, name, &text_font, 70.);
+                            }
+                        });
+
+                    for (label, value) in rows.iter() {
+                        parent
+                            .spawn(Node::default())
+                            .with_children(|parent| {
+                                spawn_cell(parent, label, &text_font, 100.);
+                                for (_, stats) in difficulties.iter() {
+                                    spawn_cell(parent, &value(stats), &text_font, 70.);
+                                }
+                            });
+                    }
+                });
+        });
+}
+
+fn on_click_close(
+    click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    query: Query<Entity, With<UIStats>>,
+) {
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// キーボード、ゲームパッドで成績画面を閉じる
+fn close_input_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    query_gamepad: Query<&Gamepad>,
+    query: Query<Entity, With<UIStats>>,
+) {
+    let close = keys.any_just_pressed([KeyCode::Escape, KeyCode::Enter, KeyCode::Space])
+        || query_gamepad.iter().any(|gamepad| {
+            gamepad.any_just_pressed([GamepadButton::South, GamepadButton::East])
+        });
+    if !close {
+        return;
+    }
+
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// 成績表のセルを追加
+fn spawn_cell(parent: &mut ChildBuilder, text: &str, text_font: &TextFont, width: f32) {
+    parent.spawn((
+        Text::new(text),
+        text_font.clone(),
+        TextColor(Color::BLACK),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Node {
+            width: Val::Px(width),
+            ..Default::default()
+        },
+    ));
+}
+
+/// 時間の表記を取得
+pub fn format_time(time: f32) -> String {
+    let time = time as u32;
+    format!("{:01}:{:02}:{:02}", time / 60 / 60, time / 60 % 60, time % 60)
+}
@@ -0,0 +1,11 @@
+/// 2列目以降に表向きで配る枚数
+pub const NUM_YUKON_FACEUP: u32 = 5;
+
+/// 列ごとに配る裏向きと表向きのカード枚数を取得（1列目は表向き1枚だけ）
+pub fn deal_counts(pile_index: u32) -> (u32, u32) {
+    if pile_index == 0 {
+        (0, 1)
+    } else {
+        (pile_index, NUM_YUKON_FACEUP)
+    }
+}